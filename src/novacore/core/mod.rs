@@ -1,27 +1,64 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
-use fxhash::FxHashMap as HashMap;
+use fxhash::{FxBuildHasher, FxHashMap as HashMap, FxHasher};
+use indexmap::IndexMap;
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive};
 
-use super::evaluator::Evaluator;
+use super::{core_ops::record, evaluator::Evaluator};
 
 pub type CallBack = fn(eval: &mut Evaluator);
 pub type Instructions = Rc<Vec<Token>>;
+// A dict keeps its keys in the order they were first put in
+pub type DictMap = IndexMap<Token, Token, FxBuildHasher>;
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum Block {
     Literal(Instructions),
     Lambda(Instructions),
     Function(Instructions, Instructions),
+    // a function read from a struct with dot syntax, which is called with the
+    // struct as self
+    Method(Rc<HashMap<String, Token>>, Instructions, Instructions),
     List(Instructions),
     Struct(Rc<HashMap<String, Token>>),
+    Dict(Rc<DictMap>),
+    Iter(Rc<Iter>),
 }
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+// A lazy sequence that for, each and collect walk one value at a time
+#[derive(PartialEq, Clone, Debug, Hash)]
+pub enum Iter {
+    // start, end and step, with the end included
+    Range(i128, i128, i128),
+    Items(Instructions),
+    Map(Rc<Iter>, Token),
+    Filter(Rc<Iter>, Token),
+    TakeWhile(Rc<Iter>, Token),
+    Take(Rc<Iter>, usize),
+}
+
+// An operator declared with infix(<> 9 left concat). Using it calls the
+// function by that name with the values on either side
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct UserOp {
+    pub symbol: String,
+    pub precedence: usize,
+    pub left: bool,
+    pub function: String,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum Operator {
     VariableAssign,
+    ConstAssign,
+    ArgStart,
     BindVar,
     New,
     AccessCall,
+    AccessPath,
     ModuleCall,
     UserFunctionChain,
     StoreTemp,
@@ -29,8 +66,15 @@ pub enum Operator {
     Or,
     Not,
     Equals,
+    NotEquals,
     Gtr,
     Lss,
+    GtrEq,
+    LssEq,
+    BitAnd,
+    BitOr,
+    Shl,
+    Shr,
     Invert,
     Mod,
     Add,
@@ -42,6 +86,7 @@ pub enum Operator {
     Break,
     Continue,
     ResolveBind,
+    Infix(Rc<UserOp>),
 }
 
 #[derive(PartialEq, Clone, Debug)]
@@ -60,17 +105,24 @@ pub enum Token {
 
     // Basic Types
     Integer(i128),
+    // only used once a value no longer fits in an Integer
+    BigInteger(BigInt),
     Float(f64),
     String(String),
     Char(char),
     Symbol(char),
     Bool(bool),
+    // what a lookup gives when it finds nothing
+    None,
 
     // Raw type
     Block(Block),
 
     // Registor code
     Reg(Vec<usize>, usize),
+
+    // Assignment target of set( such as s.pos.x
+    Path(Vec<Token>),
 }
 
 impl Token {
@@ -81,6 +133,22 @@ impl Token {
         }
     }
 
+    pub fn get_bigint(&self) -> Option<BigInt> {
+        match self {
+            Token::Integer(num) => Some(BigInt::from(*num)),
+            Token::BigInteger(num) => Some(num.clone()),
+            _ => None,
+        }
+    }
+
+    // Integers move back to i128 as soon as they fit again
+    pub fn from_bigint(num: BigInt) -> Token {
+        match i128::try_from(&num) {
+            Ok(num) => Token::Integer(num),
+            Err(_) => Token::BigInteger(num),
+        }
+    }
+
     pub fn _get_string(&self) -> &str {
         match self {
             Token::String(value) => value,
@@ -95,20 +163,57 @@ impl Token {
         }
     }
 
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Token::Id(_) => "id",
+            Token::Function(_, _) => "builtin",
+            Token::BlockCall(_, _) => "call",
+            Token::Op(_, _) => "op",
+            Token::Integer(_) | Token::BigInteger(_) => "int",
+            Token::Float(_) => "float",
+            Token::String(_) => "string",
+            Token::Char(_) => "char",
+            Token::Symbol(_) => "symbol",
+            Token::Bool(_) => "bool",
+            Token::None => "none",
+            Token::Block(block) => match block {
+                Block::Literal(_) => "block",
+                Block::Lambda(_) => "lambda",
+                Block::Function(..) | Block::Method(..) => "function",
+                Block::List(_) => "list",
+                Block::Struct(_) => "struct",
+                Block::Dict(_) => "dict",
+                Block::Iter(iter) => match **iter {
+                    Iter::Range(..) => "range",
+                    _ => "iter",
+                },
+            },
+            Token::Reg(_, _) => "reg",
+            Token::Path(_) => "path",
+        }
+    }
+
     pub fn precedence(&self) -> usize {
         match self {
-            Token::Op(Operator::VariableAssign, _) => 2,
+            Token::Op(Operator::VariableAssign, _) | Token::Op(Operator::ConstAssign, _) => 2,
             Token::Op(Operator::And, _) => 6,
             Token::Op(Operator::Or, _) => 7,
             Token::Op(Operator::Not, _) => 8,
             Token::Op(Operator::Equals, _)
+            | Token::Op(Operator::NotEquals, _)
             | Token::Op(Operator::Gtr, _)
-            | Token::Op(Operator::Lss, _) => 9,
-            Token::Op(Operator::Add, _) | Token::Op(Operator::Sub, _) => 12,
+            | Token::Op(Operator::Lss, _)
+            | Token::Op(Operator::GtrEq, _)
+            | Token::Op(Operator::LssEq, _) => 9,
+            Token::Op(Operator::BitOr, _) => 10,
+            Token::Op(Operator::BitAnd, _) => 11,
+            Token::Op(Operator::Shl, _) | Token::Op(Operator::Shr, _) => 12,
+            Token::Op(Operator::Add, _) | Token::Op(Operator::Sub, _) => 13,
             Token::Op(Operator::Mul, _)
             | Token::Op(Operator::Div, _)
-            | Token::Op(Operator::Mod, _) => 13,
-            Token::Op(Operator::Invert, _) => 15,
+            | Token::Op(Operator::Mod, _) => 14,
+            Token::Op(Operator::Invert, _) => 16,
+            Token::Op(Operator::Infix(op), _) => op.precedence,
             _ => 0,
         }
     }
@@ -116,10 +221,11 @@ impl Token {
     pub fn is_left_associative(&self) -> bool {
         match self {
             Token::Op(Operator::Invert, _) => false,
+            Token::Op(Operator::Infix(op), _) => op.left,
             Token::Op(Operator::Or, _) => true,
             Token::Op(Operator::And, _) => true,
             Token::Op(Operator::Not, _) => true,
-            Token::Op(Operator::VariableAssign, _) => false,
+            Token::Op(Operator::VariableAssign, _) | Token::Op(Operator::ConstAssign, _) => false,
             Token::Op(Operator::Add, _) | Token::Op(Operator::Sub, _) => true,
             Token::Op(Operator::Mul, _)
             | Token::Op(Operator::Div, _)
@@ -134,11 +240,13 @@ impl Token {
             Token::Function(block, _) => format!("Func[{}]", block),
             Token::BlockCall(block, _) => block.to_string(),
             Token::Integer(block) => format!("{}", block),
+            Token::BigInteger(block) => format!("{}", block),
             Token::Float(block) => format!("{}", block),
             Token::String(block) => block.to_string(),
             Token::Char(block) => format!("{}", block),
             Token::Symbol(block) => format!("{}", block),
             Token::Bool(block) => format!("Bool[{}]", block),
+            Token::None => "none".to_string(),
             Token::Block(block) => match block {
                 Block::Literal(block) => {
                     let mut list = String::new();
@@ -170,7 +278,7 @@ impl Token {
                     }
                     list.to_string()
                 }
-                Block::Function(input, block) => {
+                Block::Function(input, block) | Block::Method(_, input, block) => {
                     let mut list = String::new();
                     list.push_str("Func{");
                     if !block.is_empty() {
@@ -211,6 +319,7 @@ impl Token {
                     }
                     list.to_string()
                 }
+                Block::Struct(block) if record::is_record(block) => record::show(block),
                 Block::Struct(block) => {
                     let mut list = String::new();
                     list.push_str("S{");
@@ -228,6 +337,30 @@ impl Token {
                     }
                     list.to_string()
                 }
+                Block::Dict(block) => {
+                    let mut list = String::new();
+                    list.push_str("D{");
+                    if !block.is_empty() {
+                        for (key, value) in block.iter() {
+                            list.push_str(&key.to_str());
+                            list.push_str(" => ");
+                            list.push_str(&value.to_str());
+                            list.push(',');
+                        }
+                        list.pop();
+                        list.push('}');
+                    } else {
+                        list.push('}');
+                    }
+                    list.to_string()
+                }
+                Block::Iter(iter) => match **iter {
+                    Iter::Range(start, end, 1) => format!("range({} {})", start, end),
+                    Iter::Range(start, end, step) => {
+                        format!("range({} {}) step {}", start, end, step)
+                    }
+                    _ => "iter".to_string(),
+                },
             },
             Token::Op(operator, _) => {
                 let op = operator;
@@ -236,6 +369,11 @@ impl Token {
             //Token::FlowFunction(block) => format!("{}", block),
             //Token::FlowUserBlockCall(block) => format!("{:?}", block),
             Token::Reg(block, _) => format!("R{:?}", block),
+            Token::Path(path) => path
+                .iter()
+                .map(|key| key.to_str())
+                .collect::<Vec<String>>()
+                .join("."),
         }
     }
 
@@ -245,16 +383,270 @@ impl Token {
             Token::Function(_, _) => format!("{:?}", self),
             Token::BlockCall(_, _) => format!("{:?}", self),
             Token::Integer(_) => format!("{:?}", self),
+            Token::BigInteger(_) => format!("{:?}", self),
             Token::Float(_) => format!("{:?}", self),
             Token::String(_) => format!("{:?}", self),
             Token::Char(_) => format!("{:?}", self),
             Token::Symbol(_) => format!("{:?}", self),
             Token::Bool(_) => format!("{:?}", self),
+            Token::None => format!("{:?}", self),
             Token::Block(_) => format!("{:?}", self),
             Token::Op(_, _) => format!("{:?}", self),
             //Token::FlowFunction(_) => format!("{:?}", self),
             //Token::FlowUserBlockCall(_) => format!("{:?}", self),
             Token::Reg(_, _) => format!("{:?}", self),
+            Token::Path(_) => format!("{:?}", self),
+        }
+    }
+}
+
+// Tokens are used as dict keys, so equal tokens must hash the same. Keys go
+// through dict_key first, which keeps NaN out, so == is an equivalence on them
+impl Eq for Token {}
+
+impl Hash for Token {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Token::Id(value) | Token::String(value) => value.hash(state),
+            Token::Function(index, _) => index.hash(state),
+            Token::BlockCall(name, _) => name.hash(state),
+            Token::Op(operator, _) => operator.hash(state),
+            Token::Integer(value) => value.hash(state),
+            Token::BigInteger(value) => value.hash(state),
+            // 0.0 and -0.0 are equal
+            Token::Float(value) => {
+                if *value == 0.0 {
+                    0.0f64.to_bits().hash(state)
+                } else {
+                    value.to_bits().hash(state)
+                }
+            }
+            Token::Char(value) | Token::Symbol(value) => value.hash(state),
+            Token::Bool(value) => value.hash(state),
+            Token::None => {}
+            Token::Block(block) => match block {
+                Block::Literal(items) | Block::Lambda(items) | Block::List(items) => {
+                    items.hash(state)
+                }
+                Block::Function(inputs, items) | Block::Method(_, inputs, items) => {
+                    inputs.hash(state);
+                    items.hash(state)
+                }
+                Block::Struct(fields) => hash_unordered(fields.iter(), state),
+                Block::Dict(pairs) => hash_unordered(pairs.iter(), state),
+                Block::Iter(iter) => iter.hash(state),
+            },
+            Token::Reg(opcodes, main) => {
+                opcodes.hash(state);
+                main.hash(state)
+            }
+            Token::Path(path) => path.hash(state),
+        }
+    }
+}
+
+// A map's entries come out in no fixed order, so each is hashed on its own and
+// the results are summed
+fn hash_unordered<'a, K: Hash + 'a, H: Hasher>(
+    entries: impl Iterator<Item = (&'a K, &'a Token)>,
+    state: &mut H,
+) {
+    let mut count = 0usize;
+    let mut sum = 0u64;
+    for entry in entries {
+        let mut hasher = FxHasher::default();
+        entry.hash(&mut hasher);
+        sum = sum.wrapping_add(hasher.finish());
+        count += 1;
+    }
+    count.hash(state);
+    sum.hash(state)
+}
+
+impl Token {
+    // Dict keys are found with ==, so a key is normalised to agree with
+    // equals: a whole float like 1.0 becomes the integer 1, including inside
+    // lists, structs and dicts. NaN equals nothing, so it can not be a key
+    pub fn dict_key(self) -> Result<Token, String> {
+        match self {
+            Token::Float(value) if value.is_nan() => Err("NaN can not be a dict key".to_string()),
+            Token::Float(value) if value.fract() == 0.0 && value.is_finite() => {
+                let whole = BigInt::from_f64(value).unwrap_or_default();
+                Ok(match whole.to_i128() {
+                    Some(whole) => Token::Integer(whole),
+                    None => Token::BigInteger(whole),
+                })
+            }
+            Token::Block(Block::List(items)) => Ok(Token::Block(Block::List(Rc::new(
+                items
+                    .iter()
+                    .map(|item| item.clone().dict_key())
+                    .collect::<Result<_, _>>()?,
+            )))),
+            Token::Block(Block::Struct(fields)) => Ok(Token::Block(Block::Struct(Rc::new(
+                fields
+                    .iter()
+                    .map(|(field, value)| Ok((field.clone(), value.clone().dict_key()?)))
+                    .collect::<Result<_, String>>()?,
+            )))),
+            Token::Block(Block::Dict(pairs)) => Ok(Token::Block(Block::Dict(Rc::new(
+                pairs
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), value.clone().dict_key()?)))
+                    .collect::<Result<_, String>>()?,
+            )))),
+            key => Ok(key),
+        }
+    }
+
+    pub fn is_nan(&self) -> bool {
+        matches!(self, Token::Float(value) if value.is_nan())
+    }
+
+    // Values of different kinds are ordered bool, number, char, string, list,
+    // dict, struct, then everything else
+    fn order_rank(&self) -> usize {
+        match self {
+            Token::None => 0,
+            Token::Bool(_) => 1,
+            Token::Integer(_) | Token::BigInteger(_) | Token::Float(_) => 2,
+            Token::Char(_) => 3,
+            Token::String(_) => 4,
+            Token::Block(Block::List(_)) => 5,
+            Token::Block(Block::Dict(_)) => 6,
+            Token::Block(Block::Struct(_)) => 7,
+            _ => 8,
+        }
+    }
+
+    // A total ordering over every value, used by < > <= >= and sorting. Numbers
+    // compare by value whatever their kind, lists compare item by item, and NaN
+    // sorts after every other number
+    pub fn total_cmp(&self, other: &Token) -> Ordering {
+        match (self, other) {
+            (Token::Bool(left), Token::Bool(right)) => left.cmp(right),
+            (Token::Integer(left), Token::Integer(right)) => left.cmp(right),
+            (
+                Token::Integer(_) | Token::BigInteger(_),
+                Token::Integer(_) | Token::BigInteger(_),
+            ) => self.get_bigint().cmp(&other.get_bigint()),
+            (Token::Float(left), Token::Float(right)) => left
+                .partial_cmp(right)
+                .unwrap_or_else(|| left.is_nan().cmp(&right.is_nan())),
+            (Token::Integer(_) | Token::BigInteger(_), Token::Float(right)) => {
+                cmp_int_float(self, *right)
+            }
+            (Token::Float(left), Token::Integer(_) | Token::BigInteger(_)) => {
+                cmp_int_float(other, *left).reverse()
+            }
+            (Token::Char(left), Token::Char(right)) => left.cmp(right),
+            (Token::String(left), Token::String(right)) => left.cmp(right),
+            (Token::Block(Block::List(left)), Token::Block(Block::List(right))) => {
+                cmp_items(left.iter(), right.iter())
+            }
+            (Token::Block(Block::Dict(left)), Token::Block(Block::Dict(right))) => {
+                let (left, right) = (sorted_pairs(left), sorted_pairs(right));
+                left.iter()
+                    .zip(right.iter())
+                    .map(|(a, b)| a.0.total_cmp(b.0).then_with(|| a.1.total_cmp(b.1)))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or_else(|| left.len().cmp(&right.len()))
+            }
+            (Token::Block(Block::Struct(left)), Token::Block(Block::Struct(right))) => {
+                let (left, right) = (sorted_fields(left), sorted_fields(right));
+                left.iter()
+                    .zip(right.iter())
+                    .map(|(a, b)| a.0.cmp(b.0).then_with(|| a.1.total_cmp(b.1)))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or_else(|| left.len().cmp(&right.len()))
+            }
+            _ => self
+                .order_rank()
+                .cmp(&other.order_rank())
+                .then_with(|| self.to_str().cmp(&other.to_str())),
+        }
+    }
+
+    // Equality used by == and !=, so 1 == 1.0 and structs with the same fields
+    // are equal. NaN is not equal to anything
+    pub fn equals(&self, other: &Token) -> bool {
+        match (self, other) {
+            (
+                Token::Integer(_) | Token::BigInteger(_) | Token::Float(_),
+                Token::Integer(_) | Token::BigInteger(_) | Token::Float(_),
+            ) => !self.is_nan() && !other.is_nan() && self.total_cmp(other).is_eq(),
+            (Token::Block(Block::List(left)), Token::Block(Block::List(right))) => {
+                left.len() == right.len() && left.iter().zip(right.iter()).all(|(a, b)| a.equals(b))
+            }
+            (Token::Block(Block::Struct(left)), Token::Block(Block::Struct(right))) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .all(|(key, value)| right.get(key).is_some_and(|other| value.equals(other)))
+            }
+            (Token::Block(Block::Dict(left)), Token::Block(Block::Dict(right))) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .all(|(key, value)| right.get(key).is_some_and(|other| value.equals(other)))
+            }
+            _ => self == other,
+        }
+    }
+}
+
+// Compares an integer with a float exactly, rather than through f64 where
+// large integers lose their last digits
+fn cmp_int_float(int: &Token, float: f64) -> Ordering {
+    if float.is_nan() {
+        return Ordering::Less;
+    }
+    if float.is_infinite() {
+        return if float > 0.0 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        };
+    }
+    let whole = float.floor();
+    let ordering = match int {
+        Token::Integer(value) if whole.abs() < 1e38 => value.cmp(&(whole as i128)),
+        _ => int.get_bigint().cmp(&BigInt::from_f64(whole)),
+    };
+    // a fraction puts the float just above its whole part
+    ordering.then(if float > whole {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    })
+}
+
+fn sorted_pairs(data: &DictMap) -> Vec<(&Token, &Token)> {
+    let mut pairs: Vec<(&Token, &Token)> = data.iter().collect();
+    pairs.sort_by(|a, b| a.0.total_cmp(b.0).then_with(|| a.1.total_cmp(b.1)));
+    pairs
+}
+
+fn sorted_fields(data: &HashMap<String, Token>) -> Vec<(&String, &Token)> {
+    let mut fields: Vec<(&String, &Token)> = data.iter().collect();
+    fields.sort_by(|a, b| a.0.cmp(b.0));
+    fields
+}
+
+fn cmp_items<'a>(
+    mut left: impl Iterator<Item = &'a Token>,
+    mut right: impl Iterator<Item = &'a Token>,
+) -> Ordering {
+    loop {
+        match (left.next(), right.next()) {
+            (Some(a), Some(b)) => match a.total_cmp(b) {
+                Ordering::Equal => continue,
+                ordering => return ordering,
+            },
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
         }
     }
 }
@@ -0,0 +1,154 @@
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive};
+
+use crate::novacore::{core::Token, core_ops::operator::integer_op, evaluator::Evaluator};
+
+// Negative integers act as two's complement with infinite sign bits, so
+// -1 & 255 is 255 and -8 >> 1 is -4
+
+fn bitwise_op(
+    eval: &mut Evaluator,
+    name: &str,
+    small: fn(i128, i128) -> Option<i128>,
+    big: fn(BigInt, BigInt) -> BigInt,
+) {
+    if let (Some(right), Some(left)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match integer_op(&left, &right, small, big) {
+            Some(value) => eval.state.execution_stack.push(value),
+            None => eval.state.show_error(&format!(
+                "Incorrect arguments for {}, expected two integers but got [{:?},{:?}]",
+                name, left, right
+            )),
+        }
+    } else {
+        eval.state
+            .show_error(&format!("Not enough arguments for {}", name));
+    }
+}
+
+pub fn bit_and(eval: &mut Evaluator) {
+    bitwise_op(
+        eval,
+        "&",
+        |left, right| Some(left & right),
+        |left, right| left & right,
+    )
+}
+
+pub fn bit_or(eval: &mut Evaluator) {
+    bitwise_op(
+        eval,
+        "|",
+        |left, right| Some(left | right),
+        |left, right| left | right,
+    )
+}
+
+pub fn bit_xor(eval: &mut Evaluator) {
+    bitwise_op(
+        eval,
+        "bxor",
+        |left, right| Some(left ^ right),
+        |left, right| left ^ right,
+    )
+}
+
+// A left shift makes a number this many bits longer, so a larger one is an
+// error rather than running out of memory
+const MAX_SHIFT: i128 = 1 << 24;
+
+fn shift(eval: &mut Evaluator, name: &str, left_shift: bool) {
+    if let (Some(amount), Some(value)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (value.get_bigint(), &amount) {
+            (Some(_), Token::Integer(amount)) if left_shift && *amount > MAX_SHIFT => {
+                eval.state.show_error(&format!(
+                    "Shift amount for {} is too large, got {} but the limit is {}",
+                    name, amount, MAX_SHIFT
+                ))
+            }
+            (Some(value), Token::Integer(amount)) if *amount >= 0 => {
+                let shifted = if left_shift {
+                    value << *amount as usize
+                } else {
+                    // past its length every bit is a sign bit
+                    let amount = (*amount).min(value.bits() as i128 + 1);
+                    value >> amount as usize
+                };
+                eval.state.execution_stack.push(Token::from_bigint(shifted))
+            }
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for {}, expected an integer and a positive shift but got [{:?},{:?}]",
+                name, value, amount
+            )),
+        }
+    } else {
+        eval.state
+            .show_error(&format!("Not enough arguments for {}", name));
+    }
+}
+
+pub fn shift_left(eval: &mut Evaluator) {
+    shift(eval, "<<", true)
+}
+
+pub fn shift_right(eval: &mut Evaluator) {
+    shift(eval, ">>", false)
+}
+
+pub fn bit_not(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Integer(value)) => eval.state.execution_stack.push(Token::Integer(!value)),
+        Some(Token::BigInteger(value)) => {
+            eval.state.execution_stack.push(Token::from_bigint(!value))
+        }
+        Some(value) => eval.state.show_error(&format!(
+            "Incorrect arguments for bnot, expected an integer but got [{:?}]",
+            value
+        )),
+        None => eval.state.show_error("Not enough arguments for bnot"),
+    }
+}
+
+// A negative number has endless sign bits, so its ones are counted in its
+// 128 bit two's complement, the width of an ordinary integer
+pub fn popcount(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Integer(value)) => eval
+            .state
+            .execution_stack
+            .push(Token::Integer(value.count_ones() as i128)),
+        Some(Token::BigInteger(value)) if !value.is_negative() => eval
+            .state
+            .execution_stack
+            .push(Token::Integer(value.magnitude().count_ones() as i128)),
+        Some(value) => eval.state.show_error(&format!(
+            "Incorrect arguments for popcount, expected a positive integer or a negative one that fits in 128 bits but got [{:?}]",
+            value
+        )),
+        None => eval.state.show_error("Not enough arguments for popcount"),
+    }
+}
+
+// bit(n i) is true when bit i of n is set
+pub fn bit(eval: &mut Evaluator) {
+    if let (Some(index), Some(value)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (value.get_bigint(), index.get_bigint().and_then(|i| i.to_u64())) {
+            (Some(int), Some(index)) => eval.state.execution_stack.push(Token::Bool(int.bit(index))),
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for bit, expected an integer and a positive index but got [{:?},{:?}]",
+                value, index
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for bit");
+    }
+}
@@ -1,19 +1,30 @@
-use crate::novacore::{core::Token, evaluator::Evaluator};
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive};
+
+use crate::novacore::{
+    core::{Block, Token},
+    core_ops::{protocol, record},
+    evaluator::Evaluator,
+};
 
 pub fn as_int(eval: &mut Evaluator) {
     match eval.state.get_from_heap_or_pop() {
         Some(Token::Integer(value)) => {
             eval.state.execution_stack.push(Token::Integer(value));
         }
-        Some(Token::Float(value)) => {
-            eval.state
-                .execution_stack
-                .push(Token::Integer(value as i128));
+        Some(Token::BigInteger(value)) => {
+            eval.state.execution_stack.push(Token::BigInteger(value));
         }
+        Some(Token::Float(value)) => match BigInt::from_f64(value.trunc()) {
+            Some(value) => eval.state.execution_stack.push(Token::from_bigint(value)),
+            None => eval
+                .state
+                .show_error(&format!("Could not convert [{}] to int", value)),
+        },
 
         Some(Token::String(value)) => {
-            if let Ok(value) = value.parse::<i128>() {
-                eval.state.execution_stack.push(Token::Integer(value));
+            if let Ok(value) = value.parse::<BigInt>() {
+                eval.state.execution_stack.push(Token::from_bigint(value));
             } else {
                 eval.state
                     .show_error(&format!("Could not parse, but got [{:?}]", value))
@@ -27,12 +38,12 @@ pub fn as_int(eval: &mut Evaluator) {
 
 pub fn is_int(eval: &mut Evaluator) {
     match eval.state.get_from_heap_or_pop() {
-        Some(Token::Integer(_)) => {
+        Some(Token::Integer(_)) | Some(Token::BigInteger(_)) => {
             eval.state.execution_stack.push(Token::Bool(true));
         }
 
         Some(Token::String(value)) => {
-            if value.parse::<i128>().is_ok() {
+            if value.parse::<BigInt>().is_ok() {
                 eval.state.execution_stack.push(Token::Bool(true));
             } else {
                 eval.state.execution_stack.push(Token::Bool(false));
@@ -57,6 +68,9 @@ pub fn as_char(eval: &mut Evaluator) {
 
 pub fn is_char(eval: &mut Evaluator) {
     match eval.state.get_from_heap_or_pop() {
+        Some(Token::Char(_)) => {
+            eval.state.execution_stack.push(Token::Bool(true));
+        }
         Some(Token::String(value)) => {
             if value.parse::<char>().is_ok() {
                 eval.state.execution_stack.push(Token::Bool(true));
@@ -64,19 +78,29 @@ pub fn is_char(eval: &mut Evaluator) {
                 eval.state.execution_stack.push(Token::Bool(false));
             }
         }
-        a => eval
-            .state
-            .show_error(&format!("Incorrect argument for ischar , got [{:?}]", a)),
+        _ => eval.state.execution_stack.push(Token::Bool(false)),
     }
 }
 
 pub fn as_string(eval: &mut Evaluator) {
-    match eval.state.get_from_heap_or_pop() {
+    let value = match eval.state.get_from_heap_or_pop() {
+        Some(value) => match protocol::displayed(eval, value) {
+            Some(value) => Some(value),
+            None => return,
+        },
+        None => None,
+    };
+    match value {
         Some(Token::Integer(value)) => {
             eval.state
                 .execution_stack
                 .push(Token::String(value.to_string()));
         }
+        Some(Token::BigInteger(value)) => {
+            eval.state
+                .execution_stack
+                .push(Token::String(value.to_string()));
+        }
         Some(Token::Float(value)) => {
             eval.state
                 .execution_stack
@@ -90,8 +114,151 @@ pub fn as_string(eval: &mut Evaluator) {
                 .execution_stack
                 .push(Token::String(value.to_string()));
         }
+        Some(Token::Bool(value)) => {
+            eval.state
+                .execution_stack
+                .push(Token::String(value.to_string()));
+        }
+        Some(Token::None) => {
+            eval.state
+                .execution_stack
+                .push(Token::String("none".to_string()));
+        }
+        Some(value @ Token::Block(_)) => {
+            eval.state
+                .execution_stack
+                .push(Token::String(value.to_str()));
+        }
         a => eval
             .state
             .show_error(&format!("Incorrect argument for str , got [{:?}]", a)),
     }
 }
+
+pub fn as_float(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Integer(value)) => {
+            eval.state.execution_stack.push(Token::Float(value as f64));
+        }
+        Some(Token::BigInteger(value)) => match value.to_f64() {
+            Some(value) => eval.state.execution_stack.push(Token::Float(value)),
+            None => eval
+                .state
+                .show_error(&format!("Could not convert [{}] to float", value)),
+        },
+        Some(Token::Float(value)) => {
+            eval.state.execution_stack.push(Token::Float(value));
+        }
+        Some(Token::String(value)) => {
+            if let Ok(value) = value.trim().parse::<f64>() {
+                eval.state.execution_stack.push(Token::Float(value));
+            } else {
+                eval.state
+                    .show_error(&format!("Could not parse, but got [{:?}]", value))
+            }
+        }
+        a => eval
+            .state
+            .show_error(&format!("Incorrect argument for float , got [{:?}]", a)),
+    }
+}
+
+// Numbers are true unless zero, strings must read "true" or "false"
+pub fn as_bool(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Bool(value)) => {
+            eval.state.execution_stack.push(Token::Bool(value));
+        }
+        Some(Token::Integer(value)) => {
+            eval.state.execution_stack.push(Token::Bool(value != 0));
+        }
+        Some(Token::BigInteger(_)) => {
+            eval.state.execution_stack.push(Token::Bool(true));
+        }
+        Some(Token::Float(value)) => {
+            eval.state.execution_stack.push(Token::Bool(value != 0.0));
+        }
+        Some(Token::String(value)) => {
+            if let Ok(value) = value.trim().parse::<bool>() {
+                eval.state.execution_stack.push(Token::Bool(value));
+            } else {
+                eval.state
+                    .show_error(&format!("Could not parse, but got [{:?}]", value))
+            }
+        }
+        a => eval
+            .state
+            .show_error(&format!("Incorrect argument for bool , got [{:?}]", a)),
+    }
+}
+
+pub fn type_of(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(value) => {
+            // a record value is its own type, and a variant is its enum's
+            let name = match &value {
+                Token::Block(Block::Struct(data)) if record::is_type(data) => "record",
+                Token::Block(Block::Struct(data)) => record::enum_of(data)
+                    .or(record::name_of(data))
+                    .unwrap_or("struct"),
+                value => value.type_name(),
+            };
+            eval.state
+                .execution_stack
+                .push(Token::String(name.to_string()))
+        }
+        None => eval.state.show_error("Not enough arguments for typeof"),
+    }
+}
+
+fn is_kind(eval: &mut Evaluator, name: &str, kind: fn(&Token) -> bool) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(value) => eval.state.execution_stack.push(Token::Bool(kind(&value))),
+        None => eval
+            .state
+            .show_error(&format!("Not enough arguments for {}", name)),
+    }
+}
+
+pub fn is_float(eval: &mut Evaluator) {
+    is_kind(eval, "isfloat", |value| matches!(value, Token::Float(_)))
+}
+
+pub fn is_string(eval: &mut Evaluator) {
+    is_kind(eval, "isstr", |value| matches!(value, Token::String(_)))
+}
+
+pub fn is_bool(eval: &mut Evaluator) {
+    is_kind(eval, "isbool", |value| matches!(value, Token::Bool(_)))
+}
+
+pub fn is_list(eval: &mut Evaluator) {
+    is_kind(eval, "islist", |value| {
+        matches!(value, Token::Block(Block::List(_)))
+    })
+}
+
+pub fn is_block(eval: &mut Evaluator) {
+    is_kind(eval, "isblock", |value| {
+        matches!(value, Token::Block(Block::Literal(_)))
+    })
+}
+
+// Anything that can be called with arguments
+pub fn is_func(eval: &mut Evaluator) {
+    is_kind(eval, "isfunc", |value| {
+        matches!(
+            value,
+            Token::Block(Block::Function(..))
+                | Token::Block(Block::Method(..))
+                | Token::Block(Block::Lambda(_))
+                | Token::Function(_, _)
+        )
+    })
+}
+
+pub fn is_struct(eval: &mut Evaluator) {
+    is_kind(eval, "isstruct", |value| {
+        matches!(value, Token::Block(Block::Struct(_)))
+    })
+}
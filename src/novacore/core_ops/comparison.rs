@@ -1,85 +1,87 @@
-use crate::novacore::{core::Token, evaluator::Evaluator};
+use crate::novacore::{
+    core::Token,
+    core_ops::{iter, protocol},
+    evaluator::Evaluator,
+};
 use colored::Colorize;
+use std::cmp::Ordering;
+
+// A struct with __eq decides what equal means for it. Ranges and iterators
+// are compared as the lists they make
+fn equals(eval: &mut Evaluator, left: &Token, right: &Token) -> Option<bool> {
+    match protocol::method(left, "__eq").or_else(|| protocol::method(right, "__eq")) {
+        Some(function) => protocol::test(eval, "__eq", &function, left, right),
+        None => Some(left.equals(right)),
+    }
+}
 
 pub fn equality_comparison(eval: &mut Evaluator) {
-    if let (Some(right), Some(left)) = (
-        eval.state.get_from_heap_or_pop(),
-        eval.state.get_from_heap_or_pop(),
-    ) {
-        eval.state.execution_stack.push(Token::Bool(left == right));
+    if let (Some(right), Some(left)) = (iter::pop_listed(eval), iter::pop_listed(eval)) {
+        if let Some(equal) = equals(eval, &left, &right) {
+            eval.state.execution_stack.push(Token::Bool(equal));
+        }
     } else {
         eval.state
             .show_error("Not enough arguments for equality_comparison");
     }
 }
 
-pub fn less_than_comparison(eval: &mut Evaluator) {
-    if let (Some(right), Some(left)) = (
-        eval.state.get_from_heap_or_pop(),
-        eval.state.get_from_heap_or_pop(),
-    ) {
-        match (&left, &right) {
-            (Token::Integer(left), Token::Integer(right)) => {
-                eval.state.execution_stack.push(Token::Bool(left < right));
-            }
-            (Token::Integer(ref left), Token::Float(right)) => {
-                let left = *left as f64;
-                eval.state.execution_stack.push(Token::Bool(left < *right));
-            }
-            (Token::Float(left), Token::Float(right)) => {
-                eval.state.execution_stack.push(Token::Bool(left < right));
-            }
-            (Token::Float(left), Token::Integer(ref right)) => {
-                let right = *right as f64;
-                eval.state.execution_stack.push(Token::Bool(left < &right));
-            }
-            _ => eval.state.show_error(&format!(
-                "Incorrect arguments for < , got [{:?},{:?}]",
-                left, right
-            )),
+pub fn inequality_comparison(eval: &mut Evaluator) {
+    if let (Some(right), Some(left)) = (iter::pop_listed(eval), iter::pop_listed(eval)) {
+        if let Some(equal) = equals(eval, &left, &right) {
+            eval.state.execution_stack.push(Token::Bool(!equal));
         }
     } else {
         eval.state
-            .show_error("Not enough arguments for less_than_comparison");
+            .show_error("Not enough arguments for inequality_comparison");
     }
 }
 
-pub fn greater_than_comparison(eval: &mut Evaluator) {
-    if let (Some(right), Some(left)) = (
-        eval.state.get_from_heap_or_pop(),
-        eval.state.get_from_heap_or_pop(),
-    ) {
-        match (&left, &right) {
-            (Token::Integer(left), Token::Integer(right)) => {
-                eval.state.execution_stack.push(Token::Bool(left > right));
-            }
-            (Token::Integer(ref left), Token::Float(right)) => {
-                let left = *left as f64;
-                eval.state.execution_stack.push(Token::Bool(left > *right));
-            }
-            (Token::Float(left), Token::Float(right)) => {
-                eval.state.execution_stack.push(Token::Bool(left > right));
-            }
-            (Token::Float(left), Token::Integer(ref right)) => {
-                let right = *right as f64;
-                eval.state.execution_stack.push(Token::Bool(left > &right));
-            }
-            _ => eval.state.show_error(&format!(
-                "Incorrect arguments for > , got [{:?},{:?}]",
-                left, right
-            )),
+fn ordering_comparison(eval: &mut Evaluator, symbol: &str, test: fn(Ordering) -> bool) {
+    if let (Some(right), Some(left)) = (iter::pop_listed(eval), iter::pop_listed(eval)) {
+        // a struct with __lt orders by it, asking both ways round if needed
+        if let Some(function) =
+            protocol::method(&left, "__lt").or_else(|| protocol::method(&right, "__lt"))
+        {
+            let order = match protocol::test(eval, "__lt", &function, &left, &right) {
+                Some(true) => Ordering::Less,
+                Some(false) => match protocol::test(eval, "__lt", &function, &right, &left) {
+                    Some(true) => Ordering::Greater,
+                    Some(false) => Ordering::Equal,
+                    None => return,
+                },
+                None => return,
+            };
+            eval.state.execution_stack.push(Token::Bool(test(order)));
+            return;
         }
+        // NaN is unordered, so every comparison with it is false
+        let result = !left.is_nan() && !right.is_nan() && test(left.total_cmp(&right));
+        eval.state.execution_stack.push(Token::Bool(result))
     } else {
         eval.state
-            .show_error("Not enough arguments for greater_than_comparison");
+            .show_error(&format!("Not enough arguments for {}", symbol));
     }
 }
 
+pub fn less_than_comparison(eval: &mut Evaluator) {
+    ordering_comparison(eval, "<", Ordering::is_lt)
+}
+
+pub fn greater_than_comparison(eval: &mut Evaluator) {
+    ordering_comparison(eval, ">", Ordering::is_gt)
+}
+
+pub fn less_equal_comparison(eval: &mut Evaluator) {
+    ordering_comparison(eval, "<=", Ordering::is_le)
+}
+
+pub fn greater_equal_comparison(eval: &mut Evaluator) {
+    ordering_comparison(eval, ">=", Ordering::is_ge)
+}
+
 pub fn assert_stack_test(eval: &mut Evaluator) {
-    if let (Some(right), Some(left)) = (
-        eval.state.get_from_heap_or_pop(),
-        eval.state.get_from_heap_or_pop(),
-    ) {
+    if let (Some(right), Some(left)) = (iter::pop_listed(eval), iter::pop_listed(eval)) {
         if left == right {
             println!("{}: [{:?} = {:?}]", "SUCCESS".bright_green(), left, right)
         } else {
@@ -1,47 +1,246 @@
+use std::rc::Rc;
+
 use fxhash::FxHashMap as HashMap;
 
 use crate::novacore::{
-    core::{Block, Instructions, Token},
+    core::{Block, Instructions, Iter, Operator, Token},
+    core_ops::{dict, iter, pattern, record},
     evaluator::Evaluator,
+    state::Unwind,
 };
 
 pub fn break_loop(eval: &mut Evaluator) {
-    eval.state.break_loop.push(true);
+    if eval.state.loop_labels.is_empty() {
+        eval.state.show_error("break used outside of a loop");
+        return;
+    }
+    eval.state.unwind = Some(Unwind::Break(None, None));
 }
 
 pub fn continue_loop(eval: &mut Evaluator) {
-    eval.state.continue_loop.push(true);
+    if eval.state.loop_labels.is_empty() {
+        eval.state.show_error("continue used outside of a loop");
+        return;
+    }
+    eval.state.unwind = Some(Unwind::Continue(None));
+}
+
+// break(label) break(value) break(label value)
+pub fn break_with(eval: &mut Evaluator) {
+    if eval.state.loop_labels.is_empty() {
+        eval.state.show_error("break used outside of a loop");
+        return;
+    }
+    match eval.state.execution_stack.pop() {
+        Some(Token::Id(label)) if eval.state.is_loop_label(&label) => {
+            eval.state.unwind = Some(Unwind::Break(Some(label), None));
+        }
+        Some(top) => {
+            eval.state.execution_stack.push(top);
+            if let Some(value) = eval.state.get_from_heap_or_pop() {
+                let label = match eval.state.execution_stack.last() {
+                    Some(Token::Id(label)) if eval.state.is_loop_label(label) => {
+                        Some(label.clone())
+                    }
+                    _ => None,
+                };
+                if label.is_some() {
+                    eval.state.execution_stack.pop();
+                }
+                eval.state.unwind = Some(Unwind::Break(label, Some(value)));
+            }
+        }
+        None => eval.state.show_error("Not enough arguments for break"),
+    }
+}
+
+pub fn continue_with(eval: &mut Evaluator) {
+    if eval.state.loop_labels.is_empty() {
+        eval.state.show_error("continue used outside of a loop");
+        return;
+    }
+    match eval.state.execution_stack.pop() {
+        Some(Token::Id(label)) if eval.state.is_loop_label(&label) => {
+            eval.state.unwind = Some(Unwind::Continue(Some(label)));
+        }
+        Some(token) => eval.state.show_error(&format!(
+            "Incorrect arguments for continue, [{:?}] is not the label of an enclosing loop",
+            token
+        )),
+        None => eval.state.show_error("Not enough arguments for continue"),
+    }
+}
+
+// try({ body } { handler }) runs the handler with the error on the stack
+// if anything in the body raises one. The stack is cut back to where it
+// was when the body started.
+pub fn try_statement(eval: &mut Evaluator) {
+    match (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        (Some(Token::Block(Block::Literal(handler))), Some(Token::Block(Block::Literal(body)))) => {
+            let stack = eval.state.execution_stack.len();
+            let call_stack = eval.state.call_stack.len();
+            let arg_marks = eval.state.arg_marks.len();
+            let bindings = eval.state.bindings.len();
+            let loops = eval.state.loop_labels.len();
+
+            eval.state.try_depth += 1;
+            eval.evaluate(body);
+            eval.state.try_depth -= 1;
+
+            match eval.state.unwind.take() {
+                Some(Unwind::Error(err)) => {
+                    eval.state.execution_stack.truncate(stack);
+                    eval.state.truncate_scopes(call_stack);
+                    eval.state.arg_marks.truncate(arg_marks);
+                    eval.state.bindings.truncate(bindings);
+                    eval.state.loop_labels.truncate(loops);
+                    eval.state.execution_stack.push(err);
+                    eval.evaluate(handler);
+                }
+                other => eval.state.unwind = other,
+            }
+        }
+        (a, b) => eval.state.show_error(&format!(
+            "Incorrect arguments for try, expected two blocks but got [{:?},{:?}]",
+            b, a
+        )),
+    }
+}
+
+pub fn raise(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(err) if eval.state.try_depth > 0 => eval.state.unwind = Some(Unwind::Error(err)),
+        Some(err) => eval.state.show_error(&err.to_str()),
+        None => eval.state.show_error("Not enough arguments for raise"),
+    }
+}
+
+// Names the next loop started at this call depth, so inner loops can
+// break or continue it by name.
+pub fn label(eval: &mut Evaluator) {
+    if let Some(Token::Id(label)) = eval.state.execution_stack.pop() {
+        eval.state.pending_label = Some((label, eval.state.call_stack.len()));
+    } else {
+        eval.state
+            .show_error("Incorrect arguments for label, expected an identifier");
+    }
+}
+
+fn enter_loop(eval: &mut Evaluator) -> Option<String> {
+    let depth = eval.state.call_stack.len();
+    let label = match eval.state.pending_label.take() {
+        Some((label, at)) if at == depth => Some(label),
+        pending => {
+            eval.state.pending_label = pending;
+            None
+        }
+    };
+    eval.state.loop_labels.push(label.clone());
+    label
+}
+
+fn exit_loop(eval: &mut Evaluator) {
+    eval.state.loop_labels.pop();
+}
+
+// Runs one pass of a loop body and settles any break or continue aimed at
+// this loop. Anything aimed further out is left in place to keep unwinding.
+// Returns false once the loop should stop.
+fn run_iteration(eval: &mut Evaluator, logic: &Instructions, label: &Option<String>) -> bool {
+    let bindings = eval.state.bindings.len();
+    let arg_marks = eval.state.arg_marks.len();
+    eval.evaluate(logic.clone());
+    // an unwind can skip the ; that would have popped a -> binding, or leave
+    // a call it cut short waiting for its arguments
+    eval.state.bindings.truncate(bindings);
+    eval.state.arg_marks.truncate(arg_marks);
+    settle_unwind(eval, label)
+}
+
+fn settle_unwind(eval: &mut Evaluator, label: &Option<String>) -> bool {
+    match eval.state.unwind.take() {
+        None => true,
+        Some(Unwind::Continue(target)) if target.is_none() || target == *label => true,
+        Some(Unwind::Break(target, value)) if target.is_none() || target == *label => {
+            if let Some(value) = value {
+                eval.state.execution_stack.push(value)
+            }
+            false
+        }
+        outer => {
+            eval.state.unwind = outer;
+            false
+        }
+    }
+}
+
+pub fn forever(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Block(Block::Literal(logic))) | Some(Token::Block(Block::List(logic))) => {
+            let label = enter_loop(eval);
+            while run_iteration(eval, &logic, &label) {}
+            exit_loop(eval);
+        }
+        Some(logic) => eval
+            .state
+            .show_error(&format!("Incorrect arguments for loop, got [{:?}]", logic)),
+        None => eval.state.show_error("Not enough arguments for loop"),
+    }
+}
+
+// The name of the function on top of the stack, if it is called by one
+fn called_by(eval: &Evaluator) -> Option<String> {
+    match eval.state.execution_stack.last() {
+        Some(Token::Id(name)) => Some(name.clone()),
+        _ => None,
+    }
 }
 
 pub fn block_call(eval: &mut Evaluator) {
+    // the callable itself is one of the values call( was given
+    let provided = eval
+        .state
+        .take_arg_count()
+        .map(|count| count.saturating_sub(1));
+    call_block(eval, provided)
+}
+
+// r(2) and r.2 walk a range or iterator only as far as the item asked for
+fn index_iter(eval: &mut Evaluator, iter: &Iter) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Integer(index)) => {
+            match usize::try_from(index)
+                .ok()
+                .and_then(|index| iter::nth(eval, iter, index))
+            {
+                Some(value) => eval.state.execution_stack.push(value),
+                None if eval.state.unwind.is_some() => {}
+                None => eval.state.show_error("Index out of Bounds"),
+            }
+        }
+        _ => eval.state.show_error("Incorrect arguments for list"),
+    }
+}
+
+fn call_block(eval: &mut Evaluator, provided: Option<usize>) {
+    let name = called_by(eval);
     if let Some(token) = eval.state.get_from_heap_or_pop() {
         if let Token::Block(block) = token {
             match block {
-                Block::Function(idlist, block) => {
-                    let mut variable_stack: Vec<String> = Vec::with_capacity(10);
-
-                    for toks in idlist.iter().rev() {
-                        if let Token::Id(ident) = toks {
-                            variable_stack.push(ident.clone())
-                        } else {
-                            eval.state
-                                .show_error("Can only bind identifiers in a function")
-                        }
-                    }
-
-                    // Tie each Token into the call_stack using the tokens poped
-                    let mut newscope = HashMap::default();
-                    for tokens in variable_stack {
-                        if let Some(tok) = eval.state.get_from_heap_or_pop() {
-                            newscope.insert(tokens, tok.clone());
-                        } else {
-                            eval.state.show_error("Not enough arguments")
-                        }
-                    }
-                    eval.state.call_stack.push(newscope);
-                    eval.evaluate(block);
-                    eval.state.call_stack.pop();
+                Block::Function(params, block) => {
+                    call_function(eval, &params, block, provided, name.as_deref(), None)
                 }
+                Block::Method(receiver, params, block) => call_function(
+                    eval,
+                    &params,
+                    block,
+                    provided,
+                    name.as_deref(),
+                    Some(receiver),
+                ),
                 Block::Literal(block) => eval.evaluate(block),
                 Block::List(list) => {
                     if let Some(Token::Integer(index)) = eval.state.get_from_heap_or_pop() {
@@ -54,6 +253,9 @@ pub fn block_call(eval: &mut Evaluator) {
                         eval.state.show_error("Incorrect arguments for list")
                     }
                 }
+                Block::Struct(data) if record::is_type(&data) => {
+                    record::construct(eval, &data, provided)
+                }
                 Block::Struct(data) => {
                     if let Some(Token::Id(key)) = eval.state.execution_stack.pop() {
                         if let Some(value) = data.get(&key) {
@@ -66,6 +268,7 @@ pub fn block_call(eval: &mut Evaluator) {
                         eval.state.show_error("Incorrect arguments for struct")
                     }
                 }
+                Block::Iter(iter) => index_iter(eval, &iter),
                 _ => {
                     eval.state
                         .show_error(&format!("Cant call this type [{:?}]", block));
@@ -80,36 +283,329 @@ pub fn block_call(eval: &mut Evaluator) {
     }
 }
 
+enum Kind {
+    Required,
+    Optional(Token),
+    Rest,
+}
+
+struct Param {
+    name: String,
+    kind: Kind,
+    ty: Option<&'static str>,
+}
+
+// The parameters of a function as read from its inputs, with how many are
+// required and how many have a name of their own rather than being the rest
+pub struct Signature {
+    params: Vec<Param>,
+    returns: Option<&'static str>,
+    required: usize,
+    named: usize,
+}
+
+// The type an annotation such as x:int names
+fn annotation(eval: &Evaluator, token: Option<&Token>) -> Result<&'static str, String> {
+    let name = match token {
+        Some(Token::Id(name)) => name.as_str(),
+        Some(Token::Function(index, _)) => eval.function_name(*index),
+        Some(Token::None) => return Ok("none"),
+        Some(token) => return Err(format!("Expected a type, got [{}]", token.to_str())),
+        None => return Err("Missing type after :".to_string()),
+    };
+    pattern::type_pattern(name).ok_or(format!("Unknown type [{}]", name))
+}
+
+// [a b=10 ..rest] is a, then b with a default, then a list of any others.
+// Any of them can be annotated as x:int, and [a]:int { } leaves -> int at the
+// end for the type the function gives back
+fn read_params(eval: &Evaluator, params: &Instructions) -> Result<Signature, String> {
+    let mut read: Vec<Param> = vec![];
+    let mut returns = None;
+    let mut tokens = params.iter().peekable();
+    while let Some(token) = tokens.next() {
+        let ident = match token {
+            Token::Id(ident) => ident,
+            Token::Op(Operator::BindVar, _) => {
+                returns = Some(annotation(eval, tokens.next())?);
+                continue;
+            }
+            _ => return Err("Can only bind identifiers in a function".to_string()),
+        };
+        if matches!(
+            read.last(),
+            Some(Param {
+                kind: Kind::Rest,
+                ..
+            })
+        ) {
+            return Err(format!(
+                "Parameter [{}] comes after the rest parameter",
+                ident
+            ));
+        }
+        let (name, rest) = match ident.strip_prefix("..") {
+            Some(name) => (name.to_string(), true),
+            None => (ident.to_string(), false),
+        };
+        let ty = if let Some(Token::Symbol(':')) = tokens.peek() {
+            tokens.next();
+            Some(annotation(eval, tokens.next())?)
+        } else {
+            None
+        };
+        let kind = if rest {
+            Kind::Rest
+        } else if let Some(Token::Op(Operator::VariableAssign, _)) = tokens.peek() {
+            tokens.next();
+            match tokens.next() {
+                Some(default) => Kind::Optional(default.clone()),
+                None => return Err(format!("Missing default for parameter [{}]", name)),
+            }
+        } else if matches!(
+            read.last(),
+            Some(Param {
+                kind: Kind::Optional(_),
+                ..
+            })
+        ) {
+            return Err(format!(
+                "Parameter [{}] needs a default since it follows one that has one",
+                name
+            ));
+        } else {
+            Kind::Required
+        };
+        read.push(Param { name, kind, ty });
+    }
+    let required = read
+        .iter()
+        .filter(|param| matches!(param.kind, Kind::Required))
+        .count();
+    let named = read
+        .iter()
+        .filter(|param| !matches!(param.kind, Kind::Rest))
+        .count();
+    Ok(Signature {
+        params: read,
+        returns,
+        required,
+        named,
+    })
+}
+
+// A function's inputs are the same list each time it is called, so they are
+// read the first time and kept by the list they came from
+fn cached_signature(eval: &mut Evaluator, params: &Instructions) -> Result<Rc<Signature>, String> {
+    if let Some(signature) = eval.state.signatures.get(params) {
+        return Ok(signature);
+    }
+    let signature = Rc::new(read_params(eval, params)?);
+    eval.state.signatures.insert(params, signature.clone());
+    Ok(signature)
+}
+
+fn describe(function: Option<&str>) -> String {
+    match function {
+        Some(name) => format!("function [{}]", name),
+        None => "the function".to_string(),
+    }
+}
+
+// Checks each annotated parameter against what was bound to it in the scope
+// the call just pushed. Every item of a rest parameter is checked
+fn check_params(eval: &mut Evaluator, params: &[Param], function: Option<&str>) -> bool {
+    for param in params {
+        let ty = match param.ty {
+            Some(ty) => ty,
+            None => continue,
+        };
+        let value = match eval.state.get_from_heap(&param.name) {
+            Some(value) => value,
+            None => continue,
+        };
+        let values = match (&param.kind, &value) {
+            (Kind::Rest, Token::Block(Block::List(items))) => items.to_vec(),
+            _ => vec![value],
+        };
+        if let Some(wrong) = values.iter().find(|value| value.type_name() != ty) {
+            eval.state.show_error(&format!(
+                "Parameter [{}] of {} must be {}, got {}",
+                param.name,
+                describe(function),
+                ty,
+                wrong.type_name()
+            ));
+            return false;
+        }
+    }
+    true
+}
+
+// A default is worked out each time it is needed. An identifier is looked up
+// and a block is run, so it can use the parameters before it
+fn default_value(eval: &mut Evaluator, name: &str, default: &Token) -> Option<Token> {
+    match default {
+        Token::Id(ident) => eval.state.get_from_heap(ident),
+        Token::Block(Block::Literal(code)) => {
+            let start = eval.state.execution_stack.len();
+            eval.evaluate(code.clone());
+            let value = if eval.state.unwind.is_none() && eval.state.execution_stack.len() > start {
+                eval.state.get_from_heap_or_pop()
+            } else {
+                None
+            };
+            eval.state.execution_stack.truncate(start);
+            if value.is_none() && eval.state.unwind.is_none() {
+                eval.state
+                    .show_error(&format!("The default for [{}] gave nothing", name))
+            }
+            value
+        }
+        value => Some(value.clone()),
+    }
+}
+
+// Binds the arguments of a call in a new scope and runs the body. provided is
+// how many values the call was given in its parentheses. When that is fewer
+// than the required parameters, or unknown, the rest come off the stack as
+// they always have, so 5 5 add() still works. function is the name it was
+// called by, if it had one, for type errors, and receiver is the struct a
+// method was read from, bound as self
+pub fn call_function(
+    eval: &mut Evaluator,
+    params: &Instructions,
+    body: Instructions,
+    provided: Option<usize>,
+    function: Option<&str>,
+    receiver: Option<Rc<HashMap<String, Token>>>,
+) {
+    let signature = match cached_signature(eval, params) {
+        Ok(signature) => signature,
+        Err(err) => {
+            eval.state.show_error(&err);
+            return;
+        }
+    };
+    let has_rest = signature.named < signature.params.len();
+
+    let mut count = provided.unwrap_or(0).max(signature.required);
+    if !has_rest {
+        count = count.min(signature.named);
+    }
+    if eval.state.execution_stack.len() < count {
+        eval.state.show_error("Not enough arguments");
+        return;
+    }
+    let mut args = Vec::with_capacity(count);
+    for _ in 0..count {
+        match eval.state.get_from_heap_or_pop() {
+            Some(arg) => args.push(arg),
+            None => return,
+        }
+    }
+    args.reverse();
+
+    let mut newscope = HashMap::default();
+    let mut defaults = vec![];
+    let mut args = args.into_iter();
+    for param in &signature.params {
+        match &param.kind {
+            Kind::Required => {
+                if let Some(arg) = args.next() {
+                    newscope.insert(param.name.to_string(), arg);
+                }
+            }
+            Kind::Optional(default) => match args.next() {
+                Some(arg) => {
+                    newscope.insert(param.name.to_string(), arg);
+                }
+                None => defaults.push((param.name.as_str(), default)),
+            },
+            Kind::Rest => {
+                let rest = Token::Block(Block::List(Rc::new(args.by_ref().collect())));
+                newscope.insert(param.name.to_string(), rest);
+            }
+        }
+    }
+
+    if let Some(receiver) = receiver {
+        newscope.insert("self".to_string(), Token::Block(Block::Struct(receiver)));
+    }
+    eval.state.push_scope(newscope);
+    for (ident, default) in defaults {
+        match default_value(eval, ident, default) {
+            Some(value) => eval.state.add_varaible(ident, value),
+            None => {
+                eval.state.pop_scope();
+                return;
+            }
+        }
+    }
+    if !check_params(eval, &signature.params, function) {
+        eval.state.pop_scope();
+        return;
+    }
+    let start = eval.state.execution_stack.len();
+    eval.evaluate(body);
+    if let (Some(ty), None) = (signature.returns, &eval.state.unwind) {
+        // a bare identifier left on top is looked up while its scope is here
+        let given = match eval.state.execution_stack.last().cloned() {
+            _ if eval.state.execution_stack.len() <= start => "nothing",
+            Some(Token::Id(ident)) => match eval.state.get_from_heap(&ident) {
+                Some(value) => value.type_name(),
+                None => "id",
+            },
+            Some(value) => value.type_name(),
+            None => "nothing",
+        };
+        if given != ty {
+            eval.state.show_error(&format!(
+                "Result of {} must be {}, got {}",
+                describe(function),
+                ty,
+                given
+            ));
+        }
+    }
+    eval.state.pop_scope();
+}
+
+// Calls a block or function with the given arguments for a builtin such as
+// map, giving back what it left on top of the stack. None if it left nothing
+// or a break, continue or error is on its way out
+pub fn call_value(eval: &mut Evaluator, callable: &Token, args: Vec<Token>) -> Option<Token> {
+    let start = eval.state.execution_stack.len();
+    let provided = args.len();
+    eval.state.execution_stack.extend(args);
+    eval.state.execution_stack.push(callable.clone());
+    call_block(eval, Some(provided));
+    let result = if eval.state.unwind.is_none() && eval.state.execution_stack.len() > start {
+        eval.state.get_from_heap_or_pop()
+    } else {
+        None
+    };
+    eval.state.execution_stack.truncate(start);
+    result
+}
+
 pub fn user_block_call(eval: &mut Evaluator, function_name: &str) {
+    let provided = eval.state.take_arg_count();
     if let Some(token) = eval.state.get_from_heap(function_name) {
         if let Token::Block(block) = token {
             match block {
                 Block::Literal(block) => eval.evaluate(block),
-                Block::Function(idlist, block) => {
-                    let mut variable_stack: Vec<String> = Vec::with_capacity(10);
-
-                    for toks in idlist.iter().rev() {
-                        if let Token::Id(ident) = &toks {
-                            variable_stack.push(ident.clone())
-                        } else {
-                            eval.state
-                                .show_error("Can only bind identifiers in a function")
-                        }
-                    }
-
-                    // Tie each Token into the call_stack using the tokens poped
-                    let mut newscope = HashMap::default();
-                    for tokens in variable_stack {
-                        if let Some(tok) = eval.state.get_from_heap_or_pop() {
-                            newscope.insert(tokens, tok.clone());
-                        } else {
-                            eval.state.show_error("Not enough arguments")
-                        }
-                    }
-                    eval.state.call_stack.push(newscope);
-                    eval.evaluate(block);
-                    eval.state.call_stack.pop();
+                Block::Function(params, block) => {
+                    call_function(eval, &params, block, provided, Some(function_name), None)
                 }
+                Block::Method(receiver, params, block) => call_function(
+                    eval,
+                    &params,
+                    block,
+                    provided,
+                    Some(function_name),
+                    Some(receiver),
+                ),
                 Block::List(list) => {
                     if let Some(Token::Integer(index)) = eval.state.get_from_heap_or_pop() {
                         if let Some(value) = list.get(index as usize) {
@@ -122,6 +618,9 @@ pub fn user_block_call(eval: &mut Evaluator, function_name: &str) {
                     }
                 }
                 Block::Lambda(_) => todo!(),
+                Block::Struct(data) if record::is_type(&data) => {
+                    record::construct(eval, &data, provided)
+                }
                 Block::Struct(data) => {
                     if let Some(Token::Id(key)) = eval.state.execution_stack.pop() {
                         if let Some(value) = data.get(&key) {
@@ -134,6 +633,14 @@ pub fn user_block_call(eval: &mut Evaluator, function_name: &str) {
                         eval.state.show_error("Incorrect arguments for struct")
                     }
                 }
+                Block::Dict(data) => {
+                    if let Some(key) = eval.state.get_from_heap_or_pop() {
+                        dict::lookup(eval, &data, key)
+                    } else {
+                        eval.state.show_error("Incorrect arguments for dict")
+                    }
+                }
+                Block::Iter(iter) => index_iter(eval, &iter),
             }
         } else {
             eval.state
@@ -240,29 +747,32 @@ pub fn unless_statement(eval: &mut Evaluator) {
 
 pub fn while_loop(eval: &mut Evaluator) {
     fn while_compute(eval: &mut Evaluator, test: Instructions, logic: Instructions) {
-        'out: loop {
+        let label = enter_loop(eval);
+        loop {
             // run test block
             eval.evaluate(test.clone());
+            if !settle_unwind(eval, &label) {
+                break;
+            }
 
             // get result and run logic block if true is on stack else break
-            if let Some(Token::Bool(bool)) = eval.state.get_from_heap_or_pop() {
-                if bool {
-                    for t in &*logic {
-                        eval.eval(t.clone());
-                        if !eval.state.break_loop.is_empty() {
-                            eval.state.break_loop.pop();
-                            break 'out;
-                        }
-                        if !eval.state.continue_loop.is_empty() {
-                            eval.state.continue_loop.pop();
-                            continue 'out;
-                        }
+            match eval.state.get_from_heap_or_pop() {
+                Some(Token::Bool(true)) => {
+                    if !run_iteration(eval, &logic, &label) {
+                        break;
                     }
-                } else {
+                }
+                Some(Token::Bool(false)) => break,
+                other => {
+                    eval.state.show_error(&format!(
+                        "Incorrect test result for while, expected a bool but got [{:?}]",
+                        other
+                    ));
                     break;
                 }
             }
         }
+        exit_loop(eval);
     }
 
     if let (Some(logic), Some(testing)) = (
@@ -294,19 +804,13 @@ pub fn while_loop(eval: &mut Evaluator) {
 
 pub fn times(eval: &mut Evaluator) {
     fn times_compute(eval: &mut Evaluator, logic: Instructions, times: usize) {
-        'out: for _ in 0..times {
-            for t in &*logic {
-                eval.eval(t.clone());
-                if !eval.state.break_loop.is_empty() {
-                    eval.state.break_loop.pop();
-                    break 'out;
-                }
-                if !eval.state.continue_loop.is_empty() {
-                    eval.state.continue_loop.pop();
-                    continue 'out;
-                }
+        let label = enter_loop(eval);
+        for _ in 0..times {
+            if !run_iteration(eval, &logic, &label) {
+                break;
             }
         }
+        exit_loop(eval);
     }
 
     if let (Some(logic), Some(times)) = (
@@ -317,9 +821,9 @@ pub fn times(eval: &mut Evaluator) {
             (Token::Block(logic), Token::Integer(times)) => match logic {
                 Block::Literal(logic) => times_compute(eval, logic, times as usize),
                 Block::Function(_, logic) => {
-                    eval.state.call_stack.push(HashMap::default());
+                    eval.state.push_scope(HashMap::default());
                     times_compute(eval, logic, times as usize);
-                    eval.state.call_stack.pop();
+                    eval.state.pop_scope();
                 }
                 Block::List(logic) => times_compute(eval, logic, times as usize),
                 _ => eval.state.show_error(&format!(
@@ -343,37 +847,35 @@ pub fn each(eval: &mut Evaluator) {
         eval.state.get_from_heap_or_pop(),
     ) {
         fn each_compute(eval: &mut Evaluator, items: Instructions, logic: Instructions) {
-            'out: for item in items.iter() {
+            let label = enter_loop(eval);
+            for item in items.iter() {
                 eval.state.execution_stack.push(item.clone());
-                for t in &*logic {
-                    eval.eval(t.clone());
-                    if !eval.state.break_loop.is_empty() {
-                        eval.state.break_loop.pop();
-                        break 'out;
-                    }
-                    if !eval.state.continue_loop.is_empty() {
-                        eval.state.continue_loop.pop();
-                        continue 'out;
-                    }
+                if !run_iteration(eval, &logic, &label) {
+                    break;
                 }
             }
+            exit_loop(eval);
+        }
+
+        // Values are asked for one at a time, so a range is never built
+        fn each_compute_iter(eval: &mut Evaluator, items: &Iter, logic: Instructions) {
+            let label = enter_loop(eval);
+            iter::walk(eval, items, |eval, item| {
+                eval.state.execution_stack.push(item);
+                run_iteration(eval, &logic, &label)
+            });
+            exit_loop(eval);
         }
 
         fn each_compute_string(eval: &mut Evaluator, str: String, logic: Instructions) {
-            'out: for item in str.chars() {
+            let label = enter_loop(eval);
+            for item in str.chars() {
                 eval.state.execution_stack.push(Token::Char(item));
-                for t in &*logic {
-                    eval.eval(t.clone());
-                    if !eval.state.break_loop.is_empty() {
-                        eval.state.break_loop.pop();
-                        break 'out;
-                    }
-                    if !eval.state.continue_loop.is_empty() {
-                        eval.state.continue_loop.pop();
-                        continue 'out;
-                    }
+                if !run_iteration(eval, &logic, &label) {
+                    break;
                 }
             }
+            exit_loop(eval);
         }
 
         match (&items, logic) {
@@ -381,6 +883,12 @@ pub fn each(eval: &mut Evaluator) {
                 (Block::List(items), Block::Literal(logic)) => {
                     each_compute(eval, items.clone(), logic)
                 }
+                (Block::Dict(data), Block::Literal(logic)) => {
+                    each_compute(eval, dict::pairs(data), logic)
+                }
+                (Block::Iter(items), Block::Literal(logic)) => {
+                    each_compute_iter(eval, items, logic)
+                }
                 (items, logic) => eval.state.show_error(&format!(
                     "Incorrect arguments for each, got [{:?},{:?}]",
                     items, logic
@@ -410,49 +918,39 @@ pub fn for_each(eval: &mut Evaluator) {
         list: Instructions,
         variable_name: String,
     ) {
-        'out: for variable in list.iter() {
-            match &variable {
-                Token::Id(inner_ident) => {
-                    if let Some(token) = eval.state.get_from_heap(inner_ident) {
-                        eval.state.add_varaible(&variable_name, token.clone());
-                        for t in &*block {
-                            eval.eval(t.clone());
-                            if !eval.state.break_loop.is_empty() {
-                                eval.state.break_loop.pop();
-                                eval.state.remove_varaible(&variable_name);
-                                break 'out;
-                            }
-                            if !eval.state.continue_loop.is_empty() {
-                                eval.state.continue_loop.pop();
-                                eval.state.remove_varaible(&variable_name);
-                                continue 'out;
-                            }
-                        }
-                        eval.state.remove_varaible(&variable_name);
-                    }
-                }
-                _ => {
-                    eval.state.add_varaible(&variable_name, variable.clone());
-                    for t in &*block {
-                        eval.eval(t.clone());
-                        if !eval.state.break_loop.is_empty() {
-                            eval.state.break_loop.pop();
-                            eval.state.remove_varaible(&variable_name);
-                            break 'out;
-                        }
-                        if !eval.state.continue_loop.is_empty() {
-                            eval.state.continue_loop.pop();
-                            eval.state.remove_varaible(&variable_name);
-                            continue 'out;
-                        }
-                        if eval.state.exit {
-                            continue 'out;
-                        }
-                    }
-                    eval.state.remove_varaible(&variable_name);
-                }
+        let label = enter_loop(eval);
+        for variable in list.iter() {
+            let value = match variable {
+                Token::Id(inner_ident) => match eval.state.get_from_heap(inner_ident) {
+                    Some(token) => token,
+                    None => continue,
+                },
+                _ => variable.clone(),
+            };
+            eval.state.add_varaible(&variable_name, value);
+            let keep_going = run_iteration(eval, &block, &label);
+            eval.state.remove_varaible(&variable_name);
+            if !keep_going {
+                break;
             }
         }
+        exit_loop(eval);
+    }
+
+    fn for_compute_iter(
+        eval: &mut Evaluator,
+        block: Instructions,
+        items: &Iter,
+        variable_name: String,
+    ) {
+        let label = enter_loop(eval);
+        iter::walk(eval, items, |eval, variable| {
+            eval.state.add_varaible(&variable_name, variable);
+            let keep_going = run_iteration(eval, &block, &label);
+            eval.state.remove_varaible(&variable_name);
+            keep_going
+        });
+        exit_loop(eval);
     }
 
     fn for_compute_string(
@@ -461,27 +959,17 @@ pub fn for_each(eval: &mut Evaluator) {
         str: String,
         variable_name: String,
     ) {
-        'out: for variable in str.chars() {
+        let label = enter_loop(eval);
+        for variable in str.chars() {
             eval.state
                 .add_varaible(&variable_name, Token::Char(variable));
-            for t in &*block {
-                eval.eval(t.clone());
-                if !eval.state.break_loop.is_empty() {
-                    eval.state.break_loop.pop();
-                    eval.state.remove_varaible(&variable_name);
-                    break 'out;
-                }
-                if !eval.state.continue_loop.is_empty() {
-                    eval.state.continue_loop.pop();
-                    eval.state.remove_varaible(&variable_name);
-                    continue 'out;
-                }
-                if eval.state.exit {
-                    continue 'out;
-                }
-            }
+            let keep_going = run_iteration(eval, &block, &label);
             eval.state.remove_varaible(&variable_name);
+            if !keep_going {
+                break;
+            }
         }
+        exit_loop(eval);
     }
 
     if let (Some(block), Some(list), Some(variable)) = (
@@ -495,6 +983,16 @@ pub fn for_each(eval: &mut Evaluator) {
                 Token::Block(Block::List(list)),
                 Token::Id(variable_name),
             ) => for_compute(eval, block, list, variable_name),
+            (
+                Token::Block(Block::Literal(block)),
+                Token::Block(Block::Dict(data)),
+                Token::Id(variable_name),
+            ) => for_compute(eval, block, dict::pairs(&data), variable_name),
+            (
+                Token::Block(Block::Literal(block)),
+                Token::Block(Block::Iter(items)),
+                Token::Id(variable_name),
+            ) => for_compute_iter(eval, block, &items, variable_name),
             (
                 Token::Block(Block::Literal(block)),
                 Token::String(list),
@@ -511,34 +1009,16 @@ pub fn for_each(eval: &mut Evaluator) {
 }
 
 pub fn user_chain_call(eval: &mut Evaluator) {
+    let provided = eval.state.take_arg_count();
     if let Some(token) = eval.state.auxiliary.last().cloned() {
         if let Token::Block(block) = token {
             match block {
                 Block::Literal(block) => eval.evaluate(block),
-                Block::Function(idlist, block) => {
-                    let mut variable_stack: Vec<String> = Vec::with_capacity(10);
-
-                    for toks in idlist.iter().rev() {
-                        if let Token::Id(ident) = &toks {
-                            variable_stack.push(ident.clone())
-                        } else {
-                            eval.state
-                                .show_error("Can only bind identifiers in a function")
-                        }
-                    }
-
-                    // Tie each Token into the call_stack using the tokens poped
-                    let mut newscope = HashMap::default();
-                    for tokens in variable_stack {
-                        if let Some(tok) = eval.state.get_from_heap_or_pop() {
-                            newscope.insert(tokens, tok.clone());
-                        } else {
-                            eval.state.show_error("Not enough arguments")
-                        }
-                    }
-                    eval.state.call_stack.push(newscope);
-                    eval.evaluate(block);
-                    eval.state.call_stack.pop();
+                Block::Function(params, block) => {
+                    call_function(eval, &params, block, provided, None, None)
+                }
+                Block::Method(receiver, params, block) => {
+                    call_function(eval, &params, block, provided, None, Some(receiver))
                 }
                 Block::List(list) => {
                     if let Some(Token::Integer(index)) = eval.state.get_from_heap_or_pop() {
@@ -552,6 +1032,9 @@ pub fn user_chain_call(eval: &mut Evaluator) {
                     }
                 }
                 Block::Lambda(_) => todo!(),
+                Block::Struct(data) if record::is_type(&data) => {
+                    record::construct(eval, &data, provided)
+                }
                 Block::Struct(data) => {
                     if let Some(Token::Id(key)) = eval.state.execution_stack.pop() {
                         if let Some(value) = data.get(&key) {
@@ -564,6 +1047,14 @@ pub fn user_chain_call(eval: &mut Evaluator) {
                         eval.state.show_error("Incorrect arguments for struct")
                     }
                 }
+                Block::Dict(data) => {
+                    if let Some(key) = eval.state.get_from_heap_or_pop() {
+                        dict::lookup(eval, &data, key)
+                    } else {
+                        eval.state.show_error("Incorrect arguments for dict")
+                    }
+                }
+                Block::Iter(iter) => index_iter(eval, &iter),
             }
         } else {
             eval.state.show_error(&format!(
@@ -577,6 +1068,16 @@ pub fn user_chain_call(eval: &mut Evaluator) {
     eval.state.auxiliary.pop();
 }
 
+// A function read from a struct with dot syntax is bound to it as self
+fn bind_self(receiver: &Rc<HashMap<String, Token>>, value: Token) -> Token {
+    match value {
+        Token::Block(Block::Function(inputs, body)) => {
+            Token::Block(Block::Method(receiver.clone(), inputs, body))
+        }
+        value => value,
+    }
+}
+
 pub fn get_access(eval: &mut Evaluator) {
     if let (Some(top), Some(under)) = (
         eval.state.execution_stack.pop(),
@@ -590,7 +1091,7 @@ pub fn get_access(eval: &mut Evaluator) {
 
     if let Some(Token::Block(token)) = eval.state.get_from_heap_or_pop() {
         match token {
-            Block::Function(idlist, block) => {
+            Block::Function(idlist, block) | Block::Method(_, idlist, block) => {
                 if let Some(Token::Id(content)) = eval.state.execution_stack.pop() {
                     match content.as_str() {
                         "logic" => eval
@@ -624,10 +1125,13 @@ pub fn get_access(eval: &mut Evaluator) {
                     eval.state.show_error("Incorrect arguments for list")
                 }
             }
+            Block::Iter(iter) => index_iter(eval, &iter),
             Block::Struct(data) => {
                 if let Some(Token::Id(key)) = eval.state.execution_stack.pop() {
                     if let Some(value) = data.get(&key) {
-                        eval.state.execution_stack.push(value.clone())
+                        eval.state
+                            .execution_stack
+                            .push(bind_self(&data, value.clone()))
                     } else {
                         eval.state
                             .show_error(&format!("Key does not exist [{}]", &key))
@@ -646,6 +1150,31 @@ pub fn get_access(eval: &mut Evaluator) {
     }
 }
 
+// Builds the path of a set( target such as s.pos.x without reading it
+pub fn access_path(eval: &mut Evaluator) {
+    if let (Some(key), Some(under)) = (
+        eval.state.execution_stack.pop(),
+        eval.state.execution_stack.pop(),
+    ) {
+        match (under, key) {
+            (root @ Token::Id(_), key @ (Token::Id(_) | Token::Integer(_))) => eval
+                .state
+                .execution_stack
+                .push(Token::Path(vec![root, key])),
+            (Token::Path(mut path), key @ (Token::Id(_) | Token::Integer(_))) => {
+                path.push(key);
+                eval.state.execution_stack.push(Token::Path(path))
+            }
+            (under, key) => eval.state.show_error(&format!(
+                "Incorrect path for set, got [{:?}.{:?}]",
+                under, key
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for set path");
+    }
+}
+
 pub fn module(eval: &mut Evaluator) {
     if let (Some(Token::Id(key)), Some(Token::Id(module))) = (
         eval.state.execution_stack.pop(),
@@ -670,6 +1199,8 @@ pub fn module(eval: &mut Evaluator) {
 pub fn store_temp(eval: &mut Evaluator) {
     if let Some(token) = eval.state.get_from_heap_or_pop() {
         eval.state.auxiliary.push(token);
+        // the arguments of the chained call follow
+        eval.state.mark_arguments();
     } else {
         eval.state.show_error("Not enough arguments for store_temp");
     }
@@ -684,12 +1215,25 @@ pub fn eval_top(eval: &mut Evaluator) {
 }
 
 pub fn exe(eval: &mut Evaluator) {
+    let provided = eval
+        .state
+        .take_arg_count()
+        .map(|count| count.saturating_sub(1));
+    let name = called_by(eval);
     if let Some(token) = eval.state.get_from_heap_or_pop() {
         match token {
             Token::Block(block) => match block {
-                Block::Function(_, block) => {
-                    eval.evaluate_function(block);
+                Block::Function(params, block) => {
+                    call_function(eval, &params, block, provided, name.as_deref(), None)
                 }
+                Block::Method(receiver, params, block) => call_function(
+                    eval,
+                    &params,
+                    block,
+                    provided,
+                    name.as_deref(),
+                    Some(receiver),
+                ),
                 Block::Literal(block) => eval.evaluate(block),
                 Block::List(list) => {
                     if let Some(Token::Integer(index)) = eval.state.get_from_heap_or_pop() {
@@ -702,6 +1246,9 @@ pub fn exe(eval: &mut Evaluator) {
                         eval.state.show_error("Incorrect arguments for list")
                     }
                 }
+                Block::Struct(data) if record::is_type(&data) => {
+                    record::construct(eval, &data, provided)
+                }
                 Block::Struct(data) => {
                     if let Some(Token::Id(key)) = eval.state.get_from_heap_or_pop() {
                         if let Some(value) = data.get(&key) {
@@ -714,6 +1261,7 @@ pub fn exe(eval: &mut Evaluator) {
                         eval.state.show_error("Incorrect arguments for struct")
                     }
                 }
+                Block::Iter(iter) => index_iter(eval, &iter),
                 _ => {
                     eval.state
                         .show_error(&format!("Cant call this type [{:?}]", block));
@@ -1,7 +1,8 @@
 use std::rc::Rc;
 
 use crate::novacore::{
-    core::{Block, Token},
+    core::{Block, Iter, Token},
+    core_ops::iter,
     evaluator::Evaluator,
 };
 
@@ -11,15 +12,10 @@ pub fn create_range(eval: &mut Evaluator) {
         eval.state.get_from_heap_or_pop(),
     ) {
         match (start, end) {
-            (Token::Integer(start), Token::Integer(end)) => {
-                let mut new_list: Vec<Token> = Vec::new();
-                for x in *start..=*end {
-                    new_list.push(Token::Integer(x));
-                }
-                eval.state
-                    .execution_stack
-                    .push(Token::Block(Block::List(Rc::new(new_list.to_vec()))));
-            }
+            (Token::Integer(start), Token::Integer(end)) => eval
+                .state
+                .execution_stack
+                .push(iter::new_iter(Iter::Range(*start, *end, 1))),
             (a, b) => eval.state.show_error(&format!(
                 "Incorrect arguments for range , got [{:?},{:?}]",
                 a, b
@@ -68,6 +64,14 @@ pub fn collect(eval: &mut Evaluator) {
                     .execution_stack
                     .push(Token::Block(Block::Literal(Rc::new(newlist))))
             }
+            Token::Block(Block::Iter(iter)) => {
+                let items = iter::collect_items(eval, &iter);
+                if eval.state.unwind.is_none() {
+                    eval.state
+                        .execution_stack
+                        .push(Token::Block(Block::List(Rc::new(items))))
+                }
+            }
             // Token::Block(Block::Function(_,list)) => {
             //     for item in list.iter() {
             //         match item {
@@ -99,13 +103,9 @@ pub fn iota(eval: &mut Evaluator) {
     if let Some(ref end) = eval.state.get_from_heap_or_pop() {
         match end {
             Token::Integer(end) => {
-                let mut new_list: Vec<Token> = Vec::new();
-                for x in 0..*end {
-                    new_list.push(Token::Integer(x));
-                }
                 eval.state
                     .execution_stack
-                    .push(Token::Block(Block::List(Rc::new(new_list.to_vec()))));
+                    .push(iter::new_iter(Iter::Range(0, end - 1, 1)))
             }
             _ => eval
                 .state
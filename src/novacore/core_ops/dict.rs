@@ -0,0 +1,259 @@
+use std::rc::Rc;
+
+use crate::novacore::{
+    core::{Block, DictMap, Token},
+    core_ops::iter,
+    evaluator::Evaluator,
+};
+
+// The value under key, if there is one. Keys are matched with ==, so 1 and
+// 1.0 find the same entry
+fn find(data: &DictMap, key: Token) -> Option<&Token> {
+    key.dict_key().ok().and_then(|key| data.get(&key))
+}
+
+// Used by get and by calling a dict like a function. A missing key gives none
+pub fn lookup(eval: &mut Evaluator, data: &DictMap, key: Token) {
+    eval.state
+        .execution_stack
+        .push(find(data, key).cloned().unwrap_or(Token::None))
+}
+
+// [k v] pairs, used by items and when looping over a dict
+pub fn pairs(data: &DictMap) -> Rc<Vec<Token>> {
+    Rc::new(
+        data.iter()
+            .map(|(key, value)| {
+                Token::Block(Block::List(Rc::new(vec![key.clone(), value.clone()])))
+            })
+            .collect(),
+    )
+}
+
+// dict_of({ key, value, ... }) pairs up what the block gives back. %[key: value]
+// becomes this when a key or value has to be worked out
+pub fn dict_of(eval: &mut Evaluator) {
+    let block = match eval.state.get_from_heap_or_pop() {
+        Some(Token::Block(Block::Literal(block))) => block,
+        Some(block) => {
+            eval.state.show_error(&format!(
+                "Incorrect arguments for dict_of, got [{:?}]",
+                block
+            ));
+            return;
+        }
+        None => {
+            eval.state.show_error("Not enough arguments for dict_of");
+            return;
+        }
+    };
+    let start = eval.state.execution_stack.len();
+    eval.evaluate(block);
+    if eval.state.unwind.is_some() {
+        return;
+    }
+    let items = eval.state.execution_stack.split_off(start);
+    if !items.len().is_multiple_of(2) {
+        eval.state.show_error(&format!(
+            "A dict needs a value for every key, got {} items",
+            items.len()
+        ));
+        return;
+    }
+    let mut data = DictMap::default();
+    let mut items = items.into_iter().map(|item| match &item {
+        Token::Id(ident) => eval.state.get_from_heap(ident).unwrap_or(item),
+        _ => item,
+    });
+    while let (Some(key), Some(value)) = (items.next(), items.next()) {
+        match key.dict_key() {
+            Ok(key) => {
+                data.insert(key, value);
+            }
+            Err(err) => {
+                eval.state.show_error(&err);
+                return;
+            }
+        }
+    }
+    eval.state
+        .execution_stack
+        .push(Token::Block(Block::Dict(Rc::new(data))))
+}
+
+pub fn get(eval: &mut Evaluator) {
+    if let (Some(key), Some(dict)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match dict {
+            Token::Block(Block::Dict(data)) => lookup(eval, &data, key),
+            // get also reads a struct field or list item that might be missing
+            Token::Block(Block::Struct(fields)) => match &key {
+                Token::Id(name) | Token::String(name) => eval
+                    .state
+                    .execution_stack
+                    .push(fields.get(name).cloned().unwrap_or(Token::None)),
+                key => eval.state.show_error(&format!(
+                    "Incorrect arguments for get, a struct field must be a name, got [{:?}]",
+                    key
+                )),
+            },
+            Token::Block(Block::List(items)) => match key {
+                Token::Integer(index) => eval.state.execution_stack.push(
+                    usize::try_from(index)
+                        .ok()
+                        .and_then(|index| items.get(index).cloned())
+                        .unwrap_or(Token::None),
+                ),
+                key => eval.state.show_error(&format!(
+                    "Incorrect arguments for get, a list index must be an integer, got [{:?}]",
+                    key
+                )),
+            },
+            Token::Block(Block::Iter(items)) => match key {
+                Token::Integer(index) => {
+                    let value = usize::try_from(index)
+                        .ok()
+                        .and_then(|index| iter::nth(eval, &items, index));
+                    if eval.state.unwind.is_none() {
+                        eval.state
+                            .execution_stack
+                            .push(value.unwrap_or(Token::None))
+                    }
+                }
+                key => eval.state.show_error(&format!(
+                    "Incorrect arguments for get, a list index must be an integer, got [{:?}]",
+                    key
+                )),
+            },
+            dict => eval.state.show_error(&format!(
+                "Incorrect arguments for get, got [{:?},{:?}]",
+                dict, key
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for get");
+    }
+}
+
+pub fn put(eval: &mut Evaluator) {
+    if let (Some(value), Some(key), Some(dict)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match dict {
+            Token::Block(Block::Dict(mut data)) => {
+                let key = match key.dict_key() {
+                    Ok(key) => key,
+                    Err(err) => {
+                        eval.state.show_error(&err);
+                        return;
+                    }
+                };
+                Rc::make_mut(&mut data).insert(key, value);
+                eval.state
+                    .execution_stack
+                    .push(Token::Block(Block::Dict(data)))
+            }
+            dict => eval.state.show_error(&format!(
+                "Incorrect arguments for put, got [{:?},{:?},{:?}]",
+                dict, key, value
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for put");
+    }
+}
+
+pub fn has(eval: &mut Evaluator) {
+    if let (Some(key), Some(dict)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match dict {
+            Token::Block(Block::Dict(data)) => eval
+                .state
+                .execution_stack
+                .push(Token::Bool(find(&data, key).is_some())),
+            dict => eval.state.show_error(&format!(
+                "Incorrect arguments for has, got [{:?},{:?}]",
+                dict, key
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for has");
+    }
+}
+
+pub fn del(eval: &mut Evaluator) {
+    if let (Some(key), Some(dict)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match dict {
+            Token::Block(Block::Dict(mut data)) => {
+                if let Ok(key) = key.dict_key() {
+                    if data.contains_key(&key) {
+                        Rc::make_mut(&mut data).shift_remove(&key);
+                    }
+                }
+                eval.state
+                    .execution_stack
+                    .push(Token::Block(Block::Dict(data)))
+            }
+            dict => eval.state.show_error(&format!(
+                "Incorrect arguments for del, got [{:?},{:?}]",
+                dict, key
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for del");
+    }
+}
+
+pub fn keys(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Block(Block::Dict(data))) => {
+            eval.state
+                .execution_stack
+                .push(Token::Block(Block::List(Rc::new(
+                    data.keys().cloned().collect(),
+                ))))
+        }
+        Some(dict) => eval
+            .state
+            .show_error(&format!("Incorrect arguments for keys, got [{:?}]", dict)),
+        None => eval.state.show_error("Not enough arguments for keys"),
+    }
+}
+
+pub fn values(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Block(Block::Dict(data))) => {
+            eval.state
+                .execution_stack
+                .push(Token::Block(Block::List(Rc::new(
+                    data.values().cloned().collect(),
+                ))))
+        }
+        Some(dict) => eval
+            .state
+            .show_error(&format!("Incorrect arguments for values, got [{:?}]", dict)),
+        None => eval.state.show_error("Not enough arguments for values"),
+    }
+}
+
+pub fn items(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Block(Block::Dict(data))) => eval
+            .state
+            .execution_stack
+            .push(Token::Block(Block::List(pairs(&data)))),
+        Some(dict) => eval
+            .state
+            .show_error(&format!("Incorrect arguments for items, got [{:?}]", dict)),
+        None => eval.state.show_error("Not enough arguments for items"),
+    }
+}
@@ -3,16 +3,24 @@ use std::io::{self, Write};
 use crate::novacore::{
     self,
     core::{Block, Token},
+    core_ops::protocol,
     evaluator::Evaluator,
     utilities::{is_string_number, trim_newline},
 };
 
 pub fn println(eval: &mut Evaluator) {
     if let Some(token) = eval.state.get_from_heap_or_pop() {
+        let token = match protocol::displayed(eval, token) {
+            Some(token) => token,
+            None => return,
+        };
         match token {
             Token::Integer(token) => {
                 print!("{}\r\n", &token);
             }
+            Token::BigInteger(token) => {
+                print!("{}\r\n", &token);
+            }
             Token::Float(token) => {
                 print!("{}\r\n", &token)
             }
@@ -25,6 +33,9 @@ pub fn println(eval: &mut Evaluator) {
             Token::Bool(token) => {
                 print!("{}\r\n", &token)
             }
+            Token::None => {
+                print!("none\r\n")
+            }
             Token::Char(token) => {
                 print!("{}\r\n", token)
             }
@@ -43,10 +54,17 @@ pub fn println(eval: &mut Evaluator) {
 
 pub fn print(eval: &mut Evaluator) {
     if let Some(token) = eval.state.get_from_heap_or_pop() {
+        let token = match protocol::displayed(eval, token) {
+            Some(token) => token,
+            None => return,
+        };
         match token {
             Token::Integer(token) => {
                 print!("{}", &token);
             }
+            Token::BigInteger(token) => {
+                print!("{}", &token);
+            }
             Token::Float(token) => {
                 print!("{}", &token)
             }
@@ -59,6 +77,9 @@ pub fn print(eval: &mut Evaluator) {
             Token::Bool(token) => {
                 print!("{}", &token)
             }
+            Token::None => {
+                print!("none")
+            }
             Token::Char(token) => {
                 print!("{}", token)
             }
@@ -75,9 +96,13 @@ pub fn print(eval: &mut Evaluator) {
     }
 }
 
+// Gives none once the input has run out
 pub fn readln(eval: &mut Evaluator) {
     let mut line = String::new();
-    std::io::stdin().read_line(&mut line).unwrap();
+    if !matches!(std::io::stdin().read_line(&mut line), Ok(read) if read > 0) {
+        eval.state.execution_stack.push(Token::None);
+        return;
+    }
     let line = trim_newline(&mut line);
     if is_string_number(&line) {
         // Float
@@ -126,7 +151,7 @@ pub fn load(eval: &mut Evaluator) {
             Err(_) => todo!(),
         };
         vm.evaluator.evaluate(vm.parser.parse(parsed).into());
-        if let Some(scope) = vm.evaluator.state.call_stack.pop() {
+        if let Some(scope) = vm.evaluator.state.pop_scope() {
             eval.state.modules.insert(id, scope);
             for (key, item) in vm.evaluator.state.modules {
                 eval.state.modules.insert(key, item);
@@ -153,7 +178,7 @@ pub fn import(eval: &mut Evaluator) {
                     Err(_) => todo!(),
                 };
                 vm.evaluator.evaluate(vm.parser.parse(parsed).into());
-                if let Some(scope) = vm.evaluator.state.call_stack.pop() {
+                if let Some(scope) = vm.evaluator.state.pop_scope() {
                     eval.state.modules.insert(module.to_string(), scope);
                     for (key, item) in vm.evaluator.state.modules {
                         eval.state.modules.insert(key, item);
@@ -0,0 +1,261 @@
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::novacore::{
+    core::{Block, Iter, Token},
+    core_ops::{control::call_value, list},
+    evaluator::Evaluator,
+};
+
+// Walks an Iter, calling the blocks of any map, filter or take_while as each
+// value is asked for, so nothing is built up front
+enum Cursor {
+    Range(Option<i128>, i128, i128),
+    Items(Rc<Vec<Token>>, usize),
+    Map(Box<Cursor>, Token),
+    Filter(Box<Cursor>, Token),
+    TakeWhile(Box<Cursor>, Token, bool),
+    Take(Box<Cursor>, usize),
+}
+
+impl Cursor {
+    fn new(iter: &Iter) -> Cursor {
+        match iter {
+            Iter::Range(start, end, step) => Cursor::Range(Some(*start), *end, *step),
+            Iter::Items(items) => Cursor::Items(items.clone(), 0),
+            Iter::Map(inner, logic) => Cursor::Map(Box::new(Cursor::new(inner)), logic.clone()),
+            Iter::Filter(inner, logic) => {
+                Cursor::Filter(Box::new(Cursor::new(inner)), logic.clone())
+            }
+            Iter::TakeWhile(inner, logic) => {
+                Cursor::TakeWhile(Box::new(Cursor::new(inner)), logic.clone(), false)
+            }
+            Iter::Take(inner, count) => Cursor::Take(Box::new(Cursor::new(inner)), *count),
+        }
+    }
+
+    // None once the values run out, or when a block fails or breaks
+    fn next(&mut self, eval: &mut Evaluator) -> Option<Token> {
+        match self {
+            Cursor::Range(next, end, step) => {
+                let value = (*next)?;
+                let done = if *step > 0 {
+                    value > *end
+                } else {
+                    value < *end
+                };
+                if done {
+                    return None;
+                }
+                *next = value.checked_add(*step);
+                Some(Token::Integer(value))
+            }
+            Cursor::Items(items, index) => {
+                let value = items.get(*index)?.clone();
+                *index += 1;
+                Some(value)
+            }
+            Cursor::Map(inner, logic) => {
+                let item = inner.next(eval)?;
+                let value = call_value(eval, logic, vec![item.clone()]);
+                if value.is_none() && eval.state.unwind.is_none() {
+                    eval.state.show_error(&format!(
+                        "The block given to map gave nothing for [{:?}]",
+                        item
+                    ));
+                }
+                value
+            }
+            Cursor::Filter(inner, logic) => loop {
+                let item = inner.next(eval)?;
+                if list::test(eval, "filter", logic, &item)? {
+                    return Some(item);
+                }
+            },
+            Cursor::TakeWhile(inner, logic, done) => {
+                if *done {
+                    return None;
+                }
+                let item = inner.next(eval)?;
+                if list::test(eval, "take_while", logic, &item)? {
+                    Some(item)
+                } else {
+                    *done = true;
+                    None
+                }
+            }
+            Cursor::Take(inner, count) => {
+                if *count == 0 {
+                    return None;
+                }
+                *count -= 1;
+                inner.next(eval)
+            }
+        }
+    }
+}
+
+// Hands each value to visit until it returns false or the values run out
+pub fn walk(
+    eval: &mut Evaluator,
+    iter: &Iter,
+    mut visit: impl FnMut(&mut Evaluator, Token) -> bool,
+) {
+    let mut cursor = Cursor::new(iter);
+    while let Some(value) = cursor.next(eval) {
+        if !visit(eval, value) {
+            break;
+        }
+    }
+}
+
+pub fn collect_items(eval: &mut Evaluator, iter: &Iter) -> Vec<Token> {
+    let mut items = vec![];
+    walk(eval, iter, |_, value| {
+        items.push(value);
+        true
+    });
+    items
+}
+
+// Builtins that need a whole list collect a lazy value first, so a range or
+// iterator works anywhere a list does. None if a block in it failed
+pub fn listed(eval: &mut Evaluator, value: Token) -> Option<Token> {
+    match value {
+        Token::Block(Block::Iter(iter)) => {
+            let items = collect_items(eval, &iter);
+            eval.state
+                .unwind
+                .is_none()
+                .then(|| Token::Block(Block::List(Rc::new(items))))
+        }
+        value => Some(value),
+    }
+}
+
+pub fn pop_listed(eval: &mut Evaluator) -> Option<Token> {
+    let value = eval.state.get_from_heap_or_pop()?;
+    listed(eval, value)
+}
+
+// The value at index, walking only as far as it
+pub fn nth(eval: &mut Evaluator, iter: &Iter, index: usize) -> Option<Token> {
+    let mut found = None;
+    let mut at = 0;
+    walk(eval, iter, |_, value| {
+        if at == index {
+            found = Some(value);
+            return false;
+        }
+        at += 1;
+        true
+    });
+    found
+}
+
+pub fn new_iter(iter: Iter) -> Token {
+    Token::Block(Block::Iter(Rc::new(iter)))
+}
+
+// iter(list) walks a list lazily, so map and filter on it build nothing
+pub fn iter(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Block(Block::List(items))) => eval
+            .state
+            .execution_stack
+            .push(new_iter(Iter::Items(items))),
+        Some(Token::Block(Block::Iter(iter))) => eval
+            .state
+            .execution_stack
+            .push(Token::Block(Block::Iter(iter))),
+        Some(list) => eval
+            .state
+            .show_error(&format!("Incorrect arguments for iter, got [{:?}]", list)),
+        None => eval.state.show_error("Not enough arguments for iter"),
+    }
+}
+
+// step(range n) keeps every nth value of a range. A negative n counts down,
+// so range(10 0) step(-2) goes 10 8 6 4 2 0
+pub fn step(eval: &mut Evaluator) {
+    if let (Some(by), Some(range)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (&range, &by) {
+            (Token::Block(Block::Iter(iter)), Token::Integer(by)) if *by != 0 => match **iter {
+                Iter::Range(start, end, step) => match step.checked_mul(*by) {
+                    Some(step) => eval
+                        .state
+                        .execution_stack
+                        .push(new_iter(Iter::Range(start, end, step))),
+                    None => eval.state.show_error("Step is too large"),
+                },
+                _ => eval.state.show_error(&format!(
+                    "Incorrect arguments for step, expected a range but got [{:?}]",
+                    range
+                )),
+            },
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for step, expected a range and a step other than 0 but got [{:?},{:?}]",
+                range, by
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for step");
+    }
+}
+
+// How many values a range makes, worked out without walking it. Counted as a
+// BigInt since the distance between two i128 bounds can be larger than one
+pub fn range_len(start: i128, end: i128, step: i128) -> BigInt {
+    let reached = if step > 0 { end >= start } else { end <= start };
+    if !reached {
+        return BigInt::from(0);
+    }
+    (BigInt::from(end) - start) / step + 1
+}
+
+// The range walked backwards, from the last value it reaches to its start.
+// None when the step can not be turned around
+pub fn reverse_range(start: i128, end: i128, step: i128) -> Option<Iter> {
+    let back = step.checked_neg()?;
+    let reached = if step > 0 { end >= start } else { end <= start };
+    if !reached {
+        return Some(Iter::Range(start, end, step));
+    }
+    // the last value lies between start and end, so it fits again
+    let last = (BigInt::from(end) - start) / step * step + start;
+    Some(Iter::Range(last.to_i128()?, start, back))
+}
+
+pub fn take_while(eval: &mut Evaluator) {
+    if let (Some(logic), Some(items)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match items {
+            Token::Block(Block::Iter(iter)) => eval
+                .state
+                .execution_stack
+                .push(new_iter(Iter::TakeWhile(iter, logic))),
+            Token::Block(Block::List(items)) => {
+                let taken =
+                    collect_items(eval, &Iter::TakeWhile(Rc::new(Iter::Items(items)), logic));
+                if eval.state.unwind.is_none() {
+                    eval.state
+                        .execution_stack
+                        .push(Token::Block(Block::List(Rc::new(taken))))
+                }
+            }
+            items => eval.state.show_error(&format!(
+                "Incorrect arguments for take_while, got [{:?},{:?}]",
+                items, logic
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for take_while");
+    }
+}
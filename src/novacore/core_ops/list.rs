@@ -1,15 +1,18 @@
 use std::rc::Rc;
 
+use fxhash::FxHashSet as HashSet;
+
 use crate::novacore::{
-    core::{Block, Token},
+    core::{Block, Instructions, Iter, Token},
+    core_ops::{
+        control::call_value,
+        iter::{self, collect_items, new_iter, walk},
+    },
     evaluator::Evaluator,
 };
 
 pub fn list_push(eval: &mut Evaluator) {
-    if let (Some(token), Some(list)) = (
-        eval.state.get_from_heap_or_pop(),
-        eval.state.get_from_heap_or_pop(),
-    ) {
+    if let (Some(token), Some(list)) = (eval.state.get_from_heap_or_pop(), iter::pop_listed(eval)) {
         match (list, token) {
             (Token::Block(Block::List(list)), token) => {
                 let mut newlist = list.to_vec();
@@ -37,7 +40,7 @@ pub fn list_push(eval: &mut Evaluator) {
 }
 
 pub fn list_pop(eval: &mut Evaluator) {
-    if let Some(list) = eval.state.get_from_heap_or_pop() {
+    if let Some(list) = iter::pop_listed(eval) {
         match list {
             Token::Block(Block::List(list)) => {
                 let mut newlist = list.to_vec();
@@ -68,22 +71,16 @@ pub fn list_pop(eval: &mut Evaluator) {
 }
 
 pub fn list_last(eval: &mut Evaluator) {
-    if let Some(list) = eval.state.get_from_heap_or_pop() {
+    if let Some(list) = iter::pop_listed(eval) {
         match list {
-            Token::Block(Block::List(list)) => {
-                if let Some(token) = list.last() {
-                    eval.state.execution_stack.push(token.clone())
-                } else {
-                    eval.state.show_error("Last failed, List is empty, ")
-                }
-            }
-            Token::String(mut string1) => {
-                if let Some(last) = string1.pop() {
-                    eval.state.execution_stack.push(Token::Char(last))
-                } else {
-                    eval.state.show_error("Last failed, String is empty, ")
-                }
-            }
+            Token::Block(Block::List(list)) => eval
+                .state
+                .execution_stack
+                .push(list.last().cloned().unwrap_or(Token::None)),
+            Token::String(mut string1) => eval
+                .state
+                .execution_stack
+                .push(string1.pop().map_or(Token::None, Token::Char)),
             list => eval
                 .state
                 .show_error(&format!("Incorrect arguments for last, got [{:?}]", list)),
@@ -97,7 +94,7 @@ pub fn list_insert(eval: &mut Evaluator) {
     if let (Some(item), Some(index), Some(list)) = (
         eval.state.get_from_heap_or_pop(),
         eval.state.get_from_heap_or_pop(),
-        eval.state.get_from_heap_or_pop(),
+        iter::pop_listed(eval),
     ) {
         match (list, index, item) {
             (Token::Block(Block::List(list)), Token::Integer(index), item) => {
@@ -145,10 +142,7 @@ pub fn list_insert(eval: &mut Evaluator) {
 }
 
 pub fn list_remove(eval: &mut Evaluator) {
-    if let (Some(index), Some(list)) = (
-        eval.state.get_from_heap_or_pop(),
-        eval.state.get_from_heap_or_pop(),
-    ) {
+    if let (Some(index), Some(list)) = (eval.state.get_from_heap_or_pop(), iter::pop_listed(eval)) {
         match (list, index) {
             (Token::Block(Block::List(list)), Token::Integer(index)) => {
                 let mut newlist = list.to_vec();
@@ -184,30 +178,493 @@ pub fn list_remove(eval: &mut Evaluator) {
     }
 }
 
-// pub fn list_remove(eval: &mut Evaluator) {
-//     todo!()
-// }
+fn new_list(items: Vec<Token>) -> Token {
+    Token::Block(Block::List(Rc::new(items)))
+}
+
+// Pops the two arguments of a builtin taking a list and one more value. An
+// iterator is collected into a list first
+fn list_and(eval: &mut Evaluator, name: &str) -> Option<(Instructions, Token)> {
+    match (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        (Some(other), Some(Token::Block(Block::List(items)))) => Some((items, other)),
+        (Some(other), Some(Token::Block(Block::Iter(iter)))) => {
+            let items = collect_items(eval, &iter);
+            eval.state.unwind.is_none().then(|| (Rc::new(items), other))
+        }
+        (Some(other), Some(list)) => {
+            eval.state.show_error(&format!(
+                "Incorrect arguments for {}, got [{:?},{:?}]",
+                name, list, other
+            ));
+            None
+        }
+        _ => {
+            eval.state
+                .show_error(&format!("Not enough arguments for {}", name));
+            None
+        }
+    }
+}
+
+// Pops a single list argument, collecting an iterator
+fn single_list(eval: &mut Evaluator, name: &str) -> Option<Instructions> {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Block(Block::List(items))) => Some(items),
+        Some(Token::Block(Block::Iter(iter))) => {
+            let items = collect_items(eval, &iter);
+            eval.state.unwind.is_none().then(|| Rc::new(items))
+        }
+        Some(list) => {
+            eval.state.show_error(&format!(
+                "Incorrect arguments for {}, got [{:?}]",
+                name, list
+            ));
+            None
+        }
+        None => {
+            eval.state
+                .show_error(&format!("Not enough arguments for {}", name));
+            None
+        }
+    }
+}
+
+// Pops a list or iterator to walk lazily, along with one more value
+fn sequence_and(eval: &mut Evaluator, name: &str) -> Option<(Iter, Token)> {
+    match (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        (Some(other), Some(Token::Block(Block::List(items)))) => Some((Iter::Items(items), other)),
+        (Some(other), Some(Token::Block(Block::Iter(iter)))) => Some(((*iter).clone(), other)),
+        (Some(other), Some(list)) => {
+            eval.state.show_error(&format!(
+                "Incorrect arguments for {}, got [{:?},{:?}]",
+                name, list, other
+            ));
+            None
+        }
+        _ => {
+            eval.state
+                .show_error(&format!("Not enough arguments for {}", name));
+            None
+        }
+    }
+}
+
+fn list_and_count(eval: &mut Evaluator, name: &str) -> Option<(Instructions, usize)> {
+    match list_and(eval, name)? {
+        (items, Token::Integer(count)) if count >= 0 => Some((items, count as usize)),
+        (items, count) => {
+            eval.state.show_error(&format!(
+                "Incorrect arguments for {}, expected a list and a count but got [{:?},{:?}]",
+                name, items, count
+            ));
+            None
+        }
+    }
+}
+
+// Runs a predicate on an item. None means it gave something other than a
+// bool, or a break or error is on its way out
+pub fn test(eval: &mut Evaluator, name: &str, predicate: &Token, item: &Token) -> Option<bool> {
+    match call_value(eval, predicate, vec![item.clone()]) {
+        Some(Token::Bool(result)) => Some(result),
+        _ if eval.state.unwind.is_some() => None,
+        other => {
+            eval.state.show_error(&format!(
+                "The block given to {} must give a bool, got [{:?}] for [{:?}]",
+                name, other, item
+            ));
+            None
+        }
+    }
+}
+
+// On an iterator, map, filter and take give back another iterator that does
+// the work as it is walked
+fn lazy_on_iter(eval: &mut Evaluator, adapt: impl FnOnce(Rc<Iter>, Token) -> Iter) -> bool {
+    let len = eval.state.execution_stack.len();
+    if len < 2 {
+        return false;
+    }
+    // the iterator may still be the name of a variable holding it
+    let iter = match eval.state.execution_stack[len - 2].clone() {
+        Token::Block(Block::Iter(iter)) => iter,
+        Token::Id(ident) => match eval.state.get_from_heap(&ident) {
+            Some(Token::Block(Block::Iter(iter))) => iter,
+            _ => return false,
+        },
+        _ => return false,
+    };
+    let other = eval.state.execution_stack[len - 1].clone();
+    eval.state.execution_stack.truncate(len - 2);
+    let other = match &other {
+        Token::Id(ident) => eval.state.get_from_heap(ident).unwrap_or(other),
+        _ => other,
+    };
+    eval.state
+        .execution_stack
+        .push(new_iter(adapt(iter, other)));
+    true
+}
+
+pub fn map(eval: &mut Evaluator) {
+    if lazy_on_iter(eval, Iter::Map) {
+        return;
+    }
+    if let Some((items, logic)) = list_and(eval, "map") {
+        let mut mapped = Vec::with_capacity(items.len());
+        for item in items.iter() {
+            match call_value(eval, &logic, vec![item.clone()]) {
+                Some(value) => mapped.push(value),
+                None if eval.state.unwind.is_some() => return,
+                None => {
+                    eval.state.show_error(&format!(
+                        "The block given to map gave nothing for [{:?}]",
+                        item
+                    ));
+                    return;
+                }
+            }
+        }
+        eval.state.execution_stack.push(new_list(mapped))
+    }
+}
+
+pub fn filter(eval: &mut Evaluator) {
+    if lazy_on_iter(eval, Iter::Filter) {
+        return;
+    }
+    if let Some((items, predicate)) = list_and(eval, "filter") {
+        let mut kept = vec![];
+        for item in items.iter() {
+            match test(eval, "filter", &predicate, item) {
+                Some(true) => kept.push(item.clone()),
+                Some(false) => {}
+                None => return,
+            }
+        }
+        eval.state.execution_stack.push(new_list(kept))
+    }
+}
+
+fn fold_items<'a>(
+    eval: &mut Evaluator,
+    name: &str,
+    mut total: Token,
+    items: impl Iterator<Item = &'a Token>,
+    logic: &Token,
+) {
+    for item in items {
+        match call_value(eval, logic, vec![total.clone(), item.clone()]) {
+            Some(value) => total = value,
+            None if eval.state.unwind.is_some() => return,
+            None => {
+                eval.state.show_error(&format!(
+                    "The block given to {} gave nothing for [{:?}]",
+                    name, item
+                ));
+                return;
+            }
+        }
+    }
+    eval.state.execution_stack.push(total)
+}
+
+// fold(list start block) calls the block with the total so far and each item
+pub fn fold(eval: &mut Evaluator) {
+    if let (Some(logic), Some(start), Some(list)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match list {
+            Token::Block(Block::List(items)) => {
+                fold_items(eval, "fold", start, items.iter(), &logic)
+            }
+            Token::Block(Block::Iter(iter)) => {
+                let items = collect_items(eval, &iter);
+                if eval.state.unwind.is_none() {
+                    fold_items(eval, "fold", start, items.iter(), &logic)
+                }
+            }
+            list => eval.state.show_error(&format!(
+                "Incorrect arguments for fold, got [{:?},{:?},{:?}]",
+                list, start, logic
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for fold");
+    }
+}
+
+// Like fold, starting from the first item
+pub fn reduce(eval: &mut Evaluator) {
+    if let Some((items, logic)) = list_and(eval, "reduce") {
+        match items.split_first() {
+            Some((first, rest)) => fold_items(eval, "reduce", first.clone(), rest.iter(), &logic),
+            None => eval.state.show_error("Cannot reduce an empty list"),
+        }
+    }
+}
 
-// pub fn list_map(eval: &mut Evaluator) {
-//     todo!()
-// }
+pub fn reverse(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::Block(Block::List(items))) => eval
+            .state
+            .execution_stack
+            .push(new_list(items.iter().rev().cloned().collect())),
+        Some(Token::String(string)) => eval
+            .state
+            .execution_stack
+            .push(Token::String(string.chars().rev().collect())),
+        Some(Token::Block(Block::Iter(iter))) => match *iter {
+            Iter::Range(start, end, step) => match iter::reverse_range(start, end, step) {
+                Some(reversed) => eval.state.execution_stack.push(new_iter(reversed)),
+                None => eval.state.show_error("Step is too large"),
+            },
+            _ => {
+                let mut items = collect_items(eval, &iter);
+                if eval.state.unwind.is_none() {
+                    items.reverse();
+                    eval.state.execution_stack.push(new_list(items))
+                }
+            }
+        },
+        Some(list) => eval.state.show_error(&format!(
+            "Incorrect arguments for reverse, got [{:?}]",
+            list
+        )),
+        None => eval.state.show_error("Not enough arguments for reverse"),
+    }
+}
 
-// pub fn list_filter(eval: &mut Evaluator) {
-//     todo!()
-// }
+// zip(a b) pairs up items until the shorter list runs out
+pub fn zip(eval: &mut Evaluator) {
+    if let Some((left, right)) = list_and(eval, "zip") {
+        match iter::listed(eval, right) {
+            None => {}
+            Some(Token::Block(Block::List(right))) => {
+                let pairs = left
+                    .iter()
+                    .zip(right.iter())
+                    .map(|(a, b)| new_list(vec![a.clone(), b.clone()]))
+                    .collect();
+                eval.state.execution_stack.push(new_list(pairs))
+            }
+            Some(right) => eval.state.show_error(&format!(
+                "Incorrect arguments for zip, got [{:?},{:?}]",
+                left, right
+            )),
+        }
+    }
+}
 
-// pub fn list_fold(eval: &mut Evaluator) {
-//     todo!()
-// }
+pub fn enumerate(eval: &mut Evaluator) {
+    if let Some(items) = single_list(eval, "enumerate") {
+        let pairs = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| new_list(vec![Token::Integer(index as i128), item.clone()]))
+            .collect();
+        eval.state.execution_stack.push(new_list(pairs))
+    }
+}
 
-// pub fn list_car(eval: &mut Evaluator) {
-//     todo!()
-// }
+// Flattens one level of nesting
+pub fn flatten(eval: &mut Evaluator) {
+    if let Some(items) = single_list(eval, "flatten") {
+        let mut flat = vec![];
+        for item in items.iter() {
+            match item {
+                Token::Block(Block::List(inner)) => flat.extend(inner.iter().cloned()),
+                item => flat.push(item.clone()),
+            }
+        }
+        eval.state.execution_stack.push(new_list(flat))
+    }
+}
 
-// pub fn cdr(eval: &mut Evaluator) {
-//     todo!()
-// }
+pub fn take(eval: &mut Evaluator) {
+    if let Some(Token::Integer(count)) = eval.state.execution_stack.last() {
+        if *count >= 0 {
+            let count = *count as usize;
+            if lazy_on_iter(eval, |iter, _| Iter::Take(iter, count)) {
+                return;
+            }
+        }
+    }
+    if let Some((items, count)) = list_and_count(eval, "take") {
+        eval.state
+            .execution_stack
+            .push(new_list(items.iter().take(count).cloned().collect()))
+    }
+}
+
+// The list version of drop, named skip since drop works on the stack
+pub fn skip(eval: &mut Evaluator) {
+    if let Some((items, count)) = list_and_count(eval, "skip") {
+        eval.state
+            .execution_stack
+            .push(new_list(items.iter().skip(count).cloned().collect()))
+    }
+}
+
+pub fn chunk(eval: &mut Evaluator) {
+    if let Some((items, size)) = list_and_count(eval, "chunk") {
+        if size == 0 {
+            eval.state.show_error("Chunk size must be more than 0");
+            return;
+        }
+        let chunks = items
+            .chunks(size)
+            .map(|chunk| new_list(chunk.to_vec()))
+            .collect();
+        eval.state.execution_stack.push(new_list(chunks))
+    }
+}
 
-// pub fn list_chop(eval: &mut Evaluator) {
-//     todo!()
-// }
+// Walks the items until the predicate gives wanted, returning the item it
+// stopped at. Err means the predicate failed
+fn first_where(
+    eval: &mut Evaluator,
+    name: &str,
+    items: &Iter,
+    predicate: &Token,
+    wanted: bool,
+) -> Result<Option<Token>, ()> {
+    let mut found = None;
+    let mut failed = false;
+    walk(eval, items, |eval, item| {
+        match test(eval, name, predicate, &item) {
+            Some(result) if result == wanted => {
+                found = Some(item);
+                false
+            }
+            Some(_) => true,
+            None => {
+                failed = true;
+                false
+            }
+        }
+    });
+    if failed || eval.state.unwind.is_some() {
+        Err(())
+    } else {
+        Ok(found)
+    }
+}
+
+pub fn any(eval: &mut Evaluator) {
+    if let Some((items, predicate)) = sequence_and(eval, "any") {
+        if let Ok(found) = first_where(eval, "any", &items, &predicate, true) {
+            eval.state
+                .execution_stack
+                .push(Token::Bool(found.is_some()))
+        }
+    }
+}
+
+pub fn all(eval: &mut Evaluator) {
+    if let Some((items, predicate)) = sequence_and(eval, "all") {
+        if let Ok(found) = first_where(eval, "all", &items, &predicate, false) {
+            eval.state
+                .execution_stack
+                .push(Token::Bool(found.is_none()))
+        }
+    }
+}
+
+// find(list block) pushes the first item the block accepts, or none, used
+// by find when it is given a list or iterator
+pub fn find_item(eval: &mut Evaluator, items: &Iter, predicate: Token) {
+    if let Ok(found) = first_where(eval, "find", items, &predicate, true) {
+        eval.state
+            .execution_stack
+            .push(found.unwrap_or(Token::None))
+    }
+}
+
+// Pushes the index of the first equal item, or none
+pub fn index_of(eval: &mut Evaluator) {
+    if let Some((items, wanted)) = list_and(eval, "index_of") {
+        let index = items
+            .iter()
+            .position(|item| item.equals(&wanted))
+            .map_or(Token::None, |index| Token::Integer(index as i128));
+        eval.state.execution_stack.push(index)
+    }
+}
+
+pub fn contains(eval: &mut Evaluator) {
+    if let (Some(wanted), Some(items)) = (eval.state.get_from_heap_or_pop(), iter::pop_listed(eval))
+    {
+        match (&items, &wanted) {
+            (Token::Block(Block::List(items)), wanted) => eval
+                .state
+                .execution_stack
+                .push(Token::Bool(items.iter().any(|item| item.equals(wanted)))),
+            (Token::String(string), Token::String(part)) => eval
+                .state
+                .execution_stack
+                .push(Token::Bool(string.contains(part.as_str()))),
+            (Token::String(string), Token::Char(char)) => eval
+                .state
+                .execution_stack
+                .push(Token::Bool(string.contains(*char))),
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for contains, got [{:?},{:?}]",
+                items, wanted
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for contains");
+    }
+}
+
+// Keeps the first of each repeated item, where items repeat if they are ==
+// as in contains. Items are seen as dict keys, which agree with ==, and NaN,
+// which is never ==, is always kept
+pub fn unique(eval: &mut Evaluator) {
+    if let Some(items) = single_list(eval, "unique") {
+        let mut seen = HashSet::default();
+        let kept = items
+            .iter()
+            .filter(|item| match (*item).clone().dict_key() {
+                Ok(key) => seen.insert(key),
+                Err(_) => true,
+            })
+            .cloned()
+            .collect();
+        eval.state.execution_stack.push(new_list(kept))
+    }
+}
+
+// sort_by(list block) orders items by the key the block gives for each one,
+// keeping equal keys in their original order
+pub fn sort_by(eval: &mut Evaluator) {
+    if let Some((items, logic)) = list_and(eval, "sort_by") {
+        let mut keyed = Vec::with_capacity(items.len());
+        for item in items.iter() {
+            match call_value(eval, &logic, vec![item.clone()]) {
+                Some(key) => keyed.push((key, item.clone())),
+                None if eval.state.unwind.is_some() => return,
+                None => {
+                    eval.state.show_error(&format!(
+                        "The block given to sort_by gave nothing for [{:?}]",
+                        item
+                    ));
+                    return;
+                }
+            }
+        }
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+        eval.state
+            .execution_stack
+            .push(new_list(keyed.into_iter().map(|(_, item)| item).collect()))
+    }
+}
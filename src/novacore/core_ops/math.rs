@@ -1,4 +1,11 @@
-use crate::novacore::{core::Token, evaluator::Evaluator};
+use num_bigint::BigInt;
+use num_traits::{Signed, Zero};
+
+use crate::novacore::{
+    core::Token,
+    core_ops::operator::{big_floor_mod, floor_mod, integer_op, is_zero},
+    evaluator::Evaluator,
+};
 
 pub fn sqrt(eval: &mut Evaluator) {
     match eval.state.get_from_heap_or_pop() {
@@ -27,10 +34,30 @@ pub fn pow(eval: &mut Evaluator) {
         eval.state.get_from_heap_or_pop(),
     ) {
         (Some(left), Some(right)) => match (&right, &left) {
-            (Token::Integer(left), Token::Integer(right)) => {
-                eval.state
-                    .execution_stack
-                    .push(Token::Integer(i128::pow(*left, *right as u32)));
+            (Token::Integer(_) | Token::BigInteger(_), Token::Integer(exponent)) => {
+                let exponent = match u32::try_from(*exponent) {
+                    Ok(exponent) => exponent,
+                    Err(_) => {
+                        eval.state.show_error(&format!(
+                            "Exponent for power must be between 0 and {}, got [{}]",
+                            u32::MAX,
+                            exponent
+                        ));
+                        return;
+                    }
+                };
+                let value = match &right {
+                    Token::Integer(base) => base.checked_pow(exponent).map(Token::Integer),
+                    _ => None,
+                };
+                match (value, right.get_bigint()) {
+                    (Some(value), _) => eval.state.execution_stack.push(value),
+                    (None, Some(base)) => eval
+                        .state
+                        .execution_stack
+                        .push(Token::from_bigint(base.pow(exponent))),
+                    (None, None) => {}
+                }
             }
             (Token::Integer(left), Token::Float(right)) => {
                 eval.state
@@ -77,3 +104,144 @@ pub fn round(eval: &mut Evaluator) {
         }
     }
 }
+
+// div and mod round toward negative infinity, quot and rem toward zero:
+// div(-7 2) is -4 and mod(-7 2) is 1, quot(-7 2) is -3 and rem(-7 2) is -1
+fn integer_division(
+    eval: &mut Evaluator,
+    name: &str,
+    small: fn(i128, i128) -> Option<i128>,
+    big: fn(BigInt, BigInt) -> BigInt,
+) {
+    if let (Some(right), Some(left)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (&left, &right) {
+            (
+                Token::Integer(_) | Token::BigInteger(_),
+                Token::Integer(_) | Token::BigInteger(_),
+            ) => {
+                if is_zero(&right) {
+                    eval.state
+                        .show_error(&format!("Division by zero in {}", name));
+                    return;
+                }
+                if let Some(value) = integer_op(&left, &right, small, big) {
+                    eval.state.execution_stack.push(value);
+                }
+            }
+            (a, b) => eval.state.show_error(&format!(
+                "Incorrect arguments for {}, expected two integers but got [{:?},{:?}]",
+                name, a, b
+            )),
+        }
+    } else {
+        eval.state
+            .show_error(&format!("Not enough arguments for {}", name));
+    }
+}
+
+pub fn floor_div(eval: &mut Evaluator) {
+    integer_division(
+        eval,
+        "div",
+        |left, right| {
+            let quot = left.checked_div(right)?;
+            if left % right != 0 && (left < 0) != (right < 0) {
+                quot.checked_sub(1)
+            } else {
+                Some(quot)
+            }
+        },
+        |left, right| {
+            let quot = &left / &right;
+            if !(&left % &right).is_zero() && left.is_negative() != right.is_negative() {
+                quot - 1
+            } else {
+                quot
+            }
+        },
+    )
+}
+
+pub fn floor_modulo(eval: &mut Evaluator) {
+    integer_division(eval, "mod", floor_mod, big_floor_mod)
+}
+
+pub fn quot(eval: &mut Evaluator) {
+    integer_division(
+        eval,
+        "quot",
+        |left, right| left.checked_div(right),
+        |left, right| left / right,
+    )
+}
+
+pub fn rem(eval: &mut Evaluator) {
+    integer_division(
+        eval,
+        "rem",
+        |left, right| left.checked_rem(right),
+        |left, right| left % right,
+    )
+}
+
+// Fixed width arithmetic on 128 bit integers, for code that wants to wrap
+// around or fail instead of growing into a big integer
+fn fixed_width(eval: &mut Evaluator, name: &str, op: fn(i128, i128) -> Option<i128>) {
+    if let (Some(right), Some(left)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (&left, &right) {
+            (Token::Integer(left), Token::Integer(right)) => match op(*left, *right) {
+                Some(value) => eval.state.execution_stack.push(Token::Integer(value)),
+                None => eval
+                    .state
+                    .show_error(&format!("{} failed for [{},{}]", name, left, right)),
+            },
+            (a, b) => eval.state.show_error(&format!(
+                "Incorrect arguments for {}, expected two 128 bit integers but got [{:?},{:?}]",
+                name, a, b
+            )),
+        }
+    } else {
+        eval.state
+            .show_error(&format!("Not enough arguments for {}", name));
+    }
+}
+
+pub fn wrapping_add(eval: &mut Evaluator) {
+    fixed_width(eval, "wrapping_add", |left, right| {
+        Some(left.wrapping_add(right))
+    })
+}
+
+pub fn wrapping_sub(eval: &mut Evaluator) {
+    fixed_width(eval, "wrapping_sub", |left, right| {
+        Some(left.wrapping_sub(right))
+    })
+}
+
+pub fn wrapping_mul(eval: &mut Evaluator) {
+    fixed_width(eval, "wrapping_mul", |left, right| {
+        Some(left.wrapping_mul(right))
+    })
+}
+
+pub fn checked_add(eval: &mut Evaluator) {
+    fixed_width(eval, "checked_add", |left, right| left.checked_add(right))
+}
+
+pub fn checked_sub(eval: &mut Evaluator) {
+    fixed_width(eval, "checked_sub", |left, right| left.checked_sub(right))
+}
+
+pub fn checked_mul(eval: &mut Evaluator) {
+    fixed_width(eval, "checked_mul", |left, right| left.checked_mul(right))
+}
+
+pub fn checked_div(eval: &mut Evaluator) {
+    fixed_width(eval, "checked_div", |left, right| left.checked_div(right))
+}
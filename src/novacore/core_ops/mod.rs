@@ -1,15 +1,22 @@
+pub mod bitwise;
 pub mod casting;
 pub mod comparison;
 pub mod control;
 pub mod create;
+pub mod dict;
 pub mod io;
+pub mod iter;
 pub mod list;
 pub mod logical;
 pub mod math;
 pub mod modifier;
 pub mod operator;
+pub mod pattern;
+pub mod protocol;
 pub mod random;
+pub mod record;
 pub mod reg;
 pub mod shuffle;
+pub mod string;
 pub mod terminal;
 pub mod time;
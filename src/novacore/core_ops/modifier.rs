@@ -4,15 +4,16 @@ use fxhash::FxHashMap as HashMap;
 
 use crate::novacore::{
     core::{Block, Instructions, Operator, Token},
+    core_ops::record,
     evaluator::Evaluator,
 };
 
 pub fn create_struct(eval: &mut Evaluator) {
     match eval.state.get_from_heap_or_pop() {
         Some(Token::Block(Block::Literal(block))) => {
-            eval.state.call_stack.push(HashMap::default());
+            eval.state.push_scope(HashMap::default());
             eval.evaluate(block);
-            if let Some(new_struct) = eval.state.call_stack.pop() {
+            if let Some(new_struct) = eval.state.pop_scope() {
                 eval.state
                     .execution_stack
                     .push(Token::Block(Block::Struct(Rc::new(new_struct))));
@@ -25,6 +26,41 @@ pub fn create_struct(eval: &mut Evaluator) {
     }
 }
 
+// with(s { x = 5 }) copies s with the fields assigned in the block replaced
+pub fn with(eval: &mut Evaluator) {
+    match (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        (
+            Some(Token::Block(Block::Literal(block))),
+            Some(Token::Block(Block::Struct(mut data))),
+        ) => {
+            eval.state.push_scope(HashMap::default());
+            eval.evaluate(block);
+            if let Some(changes) = eval.state.pop_scope() {
+                for ident in changes.keys() {
+                    if let Err(err) = record::check_field(&data, ident) {
+                        eval.state.show_error(&err);
+                        return;
+                    }
+                }
+                let fields = Rc::make_mut(&mut data);
+                for (ident, token) in changes {
+                    fields.insert(ident, token);
+                }
+                eval.state
+                    .execution_stack
+                    .push(Token::Block(Block::Struct(data)));
+            }
+        }
+        (a, b) => eval.state.show_error(&format!(
+            "Incorrect argument for with. Expected Types [Struct , Block], but got [{:?},{:?}]",
+            b, a
+        )),
+    }
+}
+
 pub fn block(eval: &mut Evaluator) {
     match eval.state.get_from_heap_or_pop() {
         Some(Token::Block(Block::List(block))) => {
@@ -1,23 +1,66 @@
 use std::rc::Rc;
 
 use fxhash::FxHashMap as HashMap;
-use modulo::Mod;
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
 
 use crate::novacore::{
-    core::{Block, Token},
+    core::{Block, Token, UserOp},
+    core_ops::{control, iter, protocol, record},
     evaluator::Evaluator,
 };
 
+// Integer arithmetic that moves to a BigInteger once i128 overflows
+pub fn integer_op(
+    left: &Token,
+    right: &Token,
+    small: fn(i128, i128) -> Option<i128>,
+    big: fn(BigInt, BigInt) -> BigInt,
+) -> Option<Token> {
+    if let (Token::Integer(left), Token::Integer(right)) = (left, right) {
+        if let Some(value) = small(*left, *right) {
+            return Some(Token::Integer(value));
+        }
+    }
+    match (left.get_bigint(), right.get_bigint()) {
+        (Some(left), Some(right)) => Some(Token::from_bigint(big(left, right))),
+        _ => None,
+    }
+}
+
+fn big_float(num: &BigInt) -> f64 {
+    num.to_f64().unwrap_or(f64::NAN)
+}
+
+pub fn is_zero(token: &Token) -> bool {
+    token.get_bigint().is_some_and(|num| num.is_zero())
+}
+
+// Modulo rounds toward negative infinity, so the result takes the sign of
+// the divisor: -7 % 3 is 2
+pub fn floor_mod(left: i128, right: i128) -> Option<i128> {
+    left.checked_rem(right)
+        .and_then(|rem| rem.checked_add(right))
+        .and_then(|rem| rem.checked_rem(right))
+}
+
+pub fn big_floor_mod(left: BigInt, right: BigInt) -> BigInt {
+    ((left % &right) + &right) % right
+}
+
 pub fn add(eval: &mut Evaluator) {
-    if let (Some(right), Some(left)) = (
-        eval.state.get_from_heap_or_pop(),
-        eval.state.get_from_heap_or_pop(),
-    ) {
+    // a range joins a list as the list it makes
+    if let (Some(right), Some(left)) = (iter::pop_listed(eval), iter::pop_listed(eval)) {
         match (&left, &right) {
-            (Token::Integer(left), Token::Integer(right)) => {
-                eval.state
-                    .execution_stack
-                    .push(Token::Integer(left + right));
+            (
+                Token::Integer(_) | Token::BigInteger(_),
+                Token::Integer(_) | Token::BigInteger(_),
+            ) => {
+                if let Some(value) =
+                    integer_op(&left, &right, |l, r| l.checked_add(r), |l, r| l + r)
+                {
+                    eval.state.execution_stack.push(value);
+                }
             }
             (Token::Integer(ref left), Token::Float(right)) => {
                 let left = *left as f64;
@@ -30,6 +73,16 @@ pub fn add(eval: &mut Evaluator) {
                 let right = *right as f64;
                 eval.state.execution_stack.push(Token::Float(left + right));
             }
+            (Token::BigInteger(left), Token::Float(right)) => {
+                eval.state
+                    .execution_stack
+                    .push(Token::Float(big_float(left) + right));
+            }
+            (Token::Float(left), Token::BigInteger(right)) => {
+                eval.state
+                    .execution_stack
+                    .push(Token::Float(left + big_float(right)));
+            }
             (Token::String(left), Token::String(right)) => {
                 eval.state
                     .execution_stack
@@ -80,6 +133,16 @@ pub fn add(eval: &mut Evaluator) {
                     .execution_stack
                     .push(Token::String(left.to_string() + right));
             }
+            (Token::String(left), Token::BigInteger(right)) => {
+                eval.state
+                    .execution_stack
+                    .push(Token::String(left.to_string() + &right.to_string()));
+            }
+            (Token::BigInteger(left), Token::String(right)) => {
+                eval.state
+                    .execution_stack
+                    .push(Token::String(left.to_string() + right));
+            }
             (Token::Char(left), Token::Integer(right)) => {
                 eval.state
                     .execution_stack
@@ -91,10 +154,14 @@ pub fn add(eval: &mut Evaluator) {
                     .push(Token::String(left.to_string() + &right.to_string()));
             }
 
-            (a, b) => eval.state.show_error(&format!(
-                "Incorrect arguments for addition. got [{:?},{:?}]",
-                a, b
-            )),
+            (a, b) => {
+                if !protocol::overload(eval, "__add", &left, &right) {
+                    eval.state.show_error(&format!(
+                        "Incorrect arguments for addition. got [{:?},{:?}]",
+                        a, b
+                    ))
+                }
+            }
         }
     } else {
         eval.state.show_error("Not enough arguments for addition")
@@ -107,6 +174,10 @@ pub fn div(eval: &mut Evaluator) {
         eval.state.get_from_heap_or_pop(),
     ) {
         match (&left, &right) {
+            (
+                Token::Integer(_) | Token::BigInteger(_),
+                Token::Integer(_) | Token::BigInteger(_),
+            ) if is_zero(&right) => eval.state.show_error("Division by zero"),
             (Token::Integer(left), Token::Integer(right)) => {
                 eval.state
                     .execution_stack
@@ -127,10 +198,32 @@ pub fn div(eval: &mut Evaluator) {
                     .execution_stack
                     .push(Token::Float(*left / *right as f64));
             }
-            (a, b) => eval.state.show_error(&format!(
-                "Incorrect arguments for division. got [{:?},{:?}]",
-                a, b
-            )),
+            (Token::BigInteger(_), Token::Integer(_) | Token::BigInteger(_))
+            | (Token::Integer(_), Token::BigInteger(_)) => {
+                if let (Some(left), Some(right)) = (left.get_bigint(), right.get_bigint()) {
+                    eval.state
+                        .execution_stack
+                        .push(Token::Float(big_float(&left) / big_float(&right)));
+                }
+            }
+            (Token::BigInteger(left), Token::Float(right)) => {
+                eval.state
+                    .execution_stack
+                    .push(Token::Float(big_float(left) / *right));
+            }
+            (Token::Float(left), Token::BigInteger(right)) => {
+                eval.state
+                    .execution_stack
+                    .push(Token::Float(*left / big_float(right)));
+            }
+            (a, b) => {
+                if !protocol::overload(eval, "__div", &left, &right) {
+                    eval.state.show_error(&format!(
+                        "Incorrect arguments for division. got [{:?},{:?}]",
+                        a, b
+                    ))
+                }
+            }
         }
     } else {
         eval.state.show_error("Not enough arguments for division")
@@ -140,8 +233,17 @@ pub fn div(eval: &mut Evaluator) {
 pub fn neg(eval: &mut Evaluator) {
     if let Some(left) = eval.state.get_from_heap_or_pop() {
         match &left {
-            Token::Integer(left) => {
-                eval.state.execution_stack.push(Token::Integer(-left));
+            Token::Integer(left) => match left.checked_neg() {
+                Some(value) => eval.state.execution_stack.push(Token::Integer(value)),
+                None => eval
+                    .state
+                    .execution_stack
+                    .push(Token::from_bigint(-BigInt::from(*left))),
+            },
+            Token::BigInteger(left) => {
+                eval.state
+                    .execution_stack
+                    .push(Token::from_bigint(-left.clone()));
             }
             Token::Float(left) => {
                 eval.state.execution_stack.push(Token::Float(-left));
@@ -161,10 +263,15 @@ pub fn sub(eval: &mut Evaluator) {
         eval.state.get_from_heap_or_pop(),
     ) {
         match (&left, &right) {
-            (Token::Integer(left), Token::Integer(right)) => {
-                eval.state
-                    .execution_stack
-                    .push(Token::Integer(left - right));
+            (
+                Token::Integer(_) | Token::BigInteger(_),
+                Token::Integer(_) | Token::BigInteger(_),
+            ) => {
+                if let Some(value) =
+                    integer_op(&left, &right, |l, r| l.checked_sub(r), |l, r| l - r)
+                {
+                    eval.state.execution_stack.push(value);
+                }
             }
             (Token::Integer(ref left), Token::Float(right)) => {
                 let left = *left as f64;
@@ -177,10 +284,24 @@ pub fn sub(eval: &mut Evaluator) {
                 let right = *right as f64;
                 eval.state.execution_stack.push(Token::Float(left - right));
             }
-            (a, b) => eval.state.show_error(&format!(
-                "Incorrect arguments for subtraction. got [{:?},{:?}]",
-                a, b
-            )),
+            (Token::BigInteger(left), Token::Float(right)) => {
+                eval.state
+                    .execution_stack
+                    .push(Token::Float(big_float(left) - right));
+            }
+            (Token::Float(left), Token::BigInteger(right)) => {
+                eval.state
+                    .execution_stack
+                    .push(Token::Float(left - big_float(right)));
+            }
+            (a, b) => {
+                if !protocol::overload(eval, "__sub", &left, &right) {
+                    eval.state.show_error(&format!(
+                        "Incorrect arguments for subtraction. got [{:?},{:?}]",
+                        a, b
+                    ))
+                }
+            }
         }
     } else {
         eval.state
@@ -194,10 +315,17 @@ pub fn modulo(eval: &mut Evaluator) {
         eval.state.get_from_heap_or_pop(),
     ) {
         match (&left, &right) {
-            (Token::Integer(left), Token::Integer(right)) => {
-                eval.state
-                    .execution_stack
-                    .push(Token::Integer(left.modulo(right)));
+            (
+                Token::Integer(_) | Token::BigInteger(_),
+                Token::Integer(_) | Token::BigInteger(_),
+            ) => {
+                if is_zero(&right) {
+                    eval.state.show_error("Modulo by zero");
+                    return;
+                }
+                if let Some(value) = integer_op(&left, &right, floor_mod, big_floor_mod) {
+                    eval.state.execution_stack.push(value);
+                }
             }
             (a, b) => eval.state.show_error(&format!(
                 "Incorrect arguments for modulo %. got [{:?},{:?}]",
@@ -215,10 +343,15 @@ pub fn mul(eval: &mut Evaluator) {
         eval.state.get_from_heap_or_pop(),
     ) {
         match (&left, &right) {
-            (Token::Integer(left), Token::Integer(right)) => {
-                eval.state
-                    .execution_stack
-                    .push(Token::Integer(left * right));
+            (
+                Token::Integer(_) | Token::BigInteger(_),
+                Token::Integer(_) | Token::BigInteger(_),
+            ) => {
+                if let Some(value) =
+                    integer_op(&left, &right, |l, r| l.checked_mul(r), |l, r| l * r)
+                {
+                    eval.state.execution_stack.push(value);
+                }
             }
             (Token::Integer(ref left), Token::Float(right)) => {
                 let left = *left as f64;
@@ -231,10 +364,24 @@ pub fn mul(eval: &mut Evaluator) {
                 let right = *right as f64;
                 eval.state.execution_stack.push(Token::Float(left * right));
             }
-            (a, b) => eval.state.show_error(&format!(
-                "Incorrect arguments for multiplication. got [{:?},{:?}]",
-                a, b
-            )),
+            (Token::BigInteger(left), Token::Float(right)) => {
+                eval.state
+                    .execution_stack
+                    .push(Token::Float(big_float(left) * right));
+            }
+            (Token::Float(left), Token::BigInteger(right)) => {
+                eval.state
+                    .execution_stack
+                    .push(Token::Float(left * big_float(right)));
+            }
+            (a, b) => {
+                if !protocol::overload(eval, "__mul", &left, &right) {
+                    eval.state.show_error(&format!(
+                        "Incorrect arguments for multiplication. got [{:?},{:?}]",
+                        a, b
+                    ))
+                }
+            }
         }
     } else {
         eval.state
@@ -265,12 +412,116 @@ pub fn variable_assign(eval: &mut Evaluator) {
     }
 }
 
+// a <> b, for an operator declared with infix, calls its function with a and
+// b. The function is looked up each time, so it can be defined after infix(
+pub fn user_operator(eval: &mut Evaluator, op: &UserOp) {
+    if let (Some(right), Some(left)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match eval.state.get_from_heap(&op.function) {
+            Some(builtin @ Token::Function(..)) => {
+                eval.state.execution_stack.push(left);
+                eval.state.execution_stack.push(right);
+                eval.eval(builtin)
+            }
+            Some(_) => {
+                let function = Token::Id(op.function.clone());
+                if let Some(result) = control::call_value(eval, &function, vec![left, right]) {
+                    eval.state.execution_stack.push(result)
+                }
+            }
+            None => {}
+        }
+    } else {
+        eval.state
+            .show_error(&format!("Not enough arguments for {}", op.symbol));
+    }
+}
+
+// name := value, or const(name value), binds a name that can not be assigned
+// again or freed in its scope
+pub fn const_assign(eval: &mut Evaluator) {
+    if let (Some(token), Some(ident)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.execution_stack.pop(),
+    ) {
+        match &ident {
+            Token::Id(identifier) => eval.state.add_constant(identifier, token),
+            _ => {
+                eval.state.show_error(&format!(
+                    "Can only declare a name as constant, got [{:?},{:?}]",
+                    ident, token
+                ));
+            }
+        }
+    } else {
+        eval.state
+            .show_error("Not enough arguments for constant declaration");
+    }
+}
+
+// Rebuilds target along keys with value at the end. Unchanged fields and
+// items are shared with the original rather than copied.
+fn assign_path(target: Token, keys: &[Token], value: Token) -> Result<Token, String> {
+    let (key, rest) = match keys.split_first() {
+        Some(split) => split,
+        None => return Ok(value),
+    };
+    match (target, key) {
+        (Token::Block(Block::Struct(mut data)), Token::Id(field)) => {
+            record::check_field(&data, field)?;
+            let updated = if rest.is_empty() {
+                value
+            } else if let Some(inner) = data.get(field) {
+                assign_path(inner.clone(), rest, value)?
+            } else {
+                return Err(format!("Key does not exist [{}]", field));
+            };
+            Rc::make_mut(&mut data).insert(field.clone(), updated);
+            Ok(Token::Block(Block::Struct(data)))
+        }
+        (Token::Block(Block::List(mut list)), Token::Integer(index)) => {
+            if let Some(inner) = list.get(*index as usize) {
+                let updated = assign_path(inner.clone(), rest, value)?;
+                Rc::make_mut(&mut list)[*index as usize] = updated;
+                Ok(Token::Block(Block::List(list)))
+            } else {
+                Err("Index out of Bounds".to_string())
+            }
+        }
+        (target, key) => Err(format!("Cannot set [{:?}] on [{}]", key, target.to_str())),
+    }
+}
+
+fn assign_to_path(eval: &mut Evaluator, path: &[Token], value: Token) {
+    if let Some(Token::Id(root)) = path.first() {
+        if let Some(target) = eval.state.get_from_heap(root) {
+            match assign_path(target, &path[1..], value) {
+                Ok(updated) => eval.state.add_varaible(root, updated),
+                Err(err) => eval.state.show_error(&err),
+            }
+        }
+    }
+}
+
 pub fn variable_assign_set(eval: &mut Evaluator) {
     if let (Some(ident), Some(token)) = (
         eval.state.execution_stack.pop(),
         eval.state.get_from_heap_or_pop(),
     ) {
         match (&token, &ident) {
+            // set(s.pos.x value)
+            (Token::Path(path), _) => {
+                let value = match &ident {
+                    Token::Id(value) => eval.state.get_from_heap(value),
+                    _ => Some(ident.clone()),
+                };
+                if let Some(value) = value {
+                    assign_to_path(eval, path, value)
+                }
+            }
+            (_, Token::Path(path)) => assign_to_path(eval, path, token.clone()),
             (Token::Id(moved), Token::Id(identifier)) => {
                 eval.state.move_varaible(moved, identifier)
             }
@@ -333,7 +584,12 @@ pub fn get_new(eval: &mut Evaluator) {
 pub fn free(eval: &mut Evaluator) {
     if let Some(token) = eval.state.execution_stack.pop() {
         if let Token::Id(ident) = token {
-            eval.state.remove_varaible(&ident)
+            if eval.state.is_constant(&ident) {
+                eval.state
+                    .show_error(&format!("Cannot free constant {}", ident))
+            } else {
+                eval.state.remove_varaible(&ident)
+            }
         }
     } else {
         eval.state.show_error("Not enough arguments for free");
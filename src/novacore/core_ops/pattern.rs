@@ -0,0 +1,394 @@
+use std::rc::Rc;
+
+use fxhash::FxHashMap as HashMap;
+
+use crate::novacore::{
+    core::{Block, Instructions, Operator, Token},
+    core_ops::{iter, record},
+    evaluator::Evaluator,
+};
+
+// match( value [
+//     0: { "zero" }
+//     int(n) if { n^ < 0 }: { "negative" }
+//     [head ..tail]: { head^ }
+//     {x [y 0]}: { x^ }
+//     shape::rect(w h): { w^ * h^ }
+//     _: { "anything else" }
+// ])
+enum Pattern {
+    Wildcard,
+    Literal(Token),
+    Bind(String),
+    Type(&'static str, Option<String>),
+    // the rest is None for an exact length, Some(None) to ignore it
+    List(Vec<Pattern>, Option<Option<String>>),
+    Struct(Vec<(String, Pattern)>),
+    // a record or variant, by enum, name and its fields in declared order
+    Record(Option<String>, String, Option<Vec<Pattern>>),
+}
+
+pub struct Arm {
+    pattern: Pattern,
+    guard: Option<Instructions>,
+    body: Instructions,
+}
+
+// Commas, and the marks calls leave for their arguments, are not patterns
+fn is_separator(token: &Token) -> bool {
+    matches!(token, Token::Symbol(',') | Token::Op(Operator::ArgStart, _))
+}
+
+pub fn type_pattern(name: &str) -> Option<&'static str> {
+    match name {
+        "int" => Some("int"),
+        "float" => Some("float"),
+        "str" | "string" => Some("string"),
+        "char" => Some("char"),
+        "bool" => Some("bool"),
+        "list" => Some("list"),
+        "block" => Some("block"),
+        "lambda" => Some("lambda"),
+        "func" | "function" => Some("function"),
+        "struct" => Some("struct"),
+        "dict" => Some("dict"),
+        "builtin" => Some("builtin"),
+        _ => None,
+    }
+}
+
+fn parse_typed(
+    eval: &Evaluator,
+    tokens: &[Token],
+    pos: &mut usize,
+    name: &str,
+) -> Result<Pattern, String> {
+    let ty = match type_pattern(name) {
+        Some(ty) => ty,
+        None => return parse_record(eval, tokens, pos, None, name),
+    };
+    match (tokens.get(*pos), tokens.get(*pos + 1), tokens.get(*pos + 2)) {
+        (Some(Token::Symbol('(')), Some(Token::Id(ident)), Some(Token::Symbol(')'))) => {
+            *pos += 3;
+            Ok(Pattern::Type(ty, Some(ident.clone())))
+        }
+        // a type name that is also a builtin, like int or float
+        (Some(token), _, _) if *token != Token::Symbol('(') => Ok(Pattern::Type(ty, None)),
+        (None, _, _) => Ok(Pattern::Type(ty, None)),
+        _ => Err(format!("Expected a name to bind in {}( )", name)),
+    }
+}
+
+// point(x y) or shape::circle(r), where each field is a pattern. A variant
+// named without ( ) only checks the variant
+fn parse_record(
+    eval: &Evaluator,
+    tokens: &[Token],
+    pos: &mut usize,
+    family: Option<&str>,
+    name: &str,
+) -> Result<Pattern, String> {
+    if let Some(Token::Op(Operator::StoreTemp, _)) = tokens.get(*pos) {
+        *pos += 1;
+    }
+    let mut fields = None;
+    if let Some(Token::Symbol('(')) = tokens.get(*pos) {
+        *pos += 1;
+        let mut patterns = vec![];
+        loop {
+            match tokens.get(*pos) {
+                Some(Token::Symbol(')')) => break,
+                Some(_) => patterns.push(parse_pattern(eval, tokens, pos)?),
+                None => return Err(format!("Missing ) in the pattern for [{}]", name)),
+            }
+        }
+        *pos += 1;
+        fields = Some(patterns);
+    }
+    Ok(Pattern::Record(
+        family.map(str::to_string),
+        name.to_string(),
+        fields,
+    ))
+}
+
+fn parse_list_pattern(eval: &Evaluator, items: &[Token]) -> Result<Pattern, String> {
+    let items: Vec<Token> = items
+        .iter()
+        .filter(|token| !is_separator(token))
+        .cloned()
+        .collect();
+    let mut patterns = vec![];
+    let mut rest = None;
+    let mut pos = 0;
+    while pos < items.len() {
+        if let Token::Id(ident) = &items[pos] {
+            if let Some(name) = ident.strip_prefix("..") {
+                if pos + 1 != items.len() {
+                    return Err(format!("Rest pattern [{}] must come last", ident));
+                }
+                rest = Some(if name.is_empty() || name == "_" {
+                    None
+                } else {
+                    Some(name.to_string())
+                });
+                break;
+            }
+        }
+        patterns.push(parse_pattern(eval, &items, &mut pos)?);
+    }
+    Ok(Pattern::List(patterns, rest))
+}
+
+fn parse_struct_pattern(eval: &Evaluator, fields: &[Token]) -> Result<Pattern, String> {
+    let mut patterns = vec![];
+    for field in fields {
+        match field {
+            Token::Id(ident) => patterns.push((ident.clone(), Pattern::Bind(ident.clone()))),
+            Token::Block(Block::List(pair)) => {
+                let pair: Vec<Token> = pair
+                    .iter()
+                    .filter(|token| !is_separator(token))
+                    .cloned()
+                    .collect();
+                if let Some(Token::Id(ident)) = pair.first() {
+                    let mut pos = 1;
+                    let pattern = parse_pattern(eval, &pair, &mut pos)?;
+                    patterns.push((ident.clone(), pattern))
+                } else {
+                    return Err(format!("Expected [field pattern], got {:?}", pair));
+                }
+            }
+            token if is_separator(token) => {}
+            field => return Err(format!("Invalid field in struct pattern {:?}", field)),
+        }
+    }
+    Ok(Pattern::Struct(patterns))
+}
+
+fn parse_pattern(eval: &Evaluator, tokens: &[Token], pos: &mut usize) -> Result<Pattern, String> {
+    let token = match tokens.get(*pos) {
+        Some(token) => token,
+        None => return Err("Missing pattern".to_string()),
+    };
+    *pos += 1;
+    match token {
+        Token::Id(ident) if ident == "_" => Ok(Pattern::Wildcard),
+        Token::Id(family)
+            if matches!(tokens.get(*pos), Some(Token::Op(Operator::ModuleCall, _))) =>
+        {
+            match tokens.get(*pos + 1) {
+                Some(Token::Id(name)) => {
+                    *pos += 2;
+                    parse_record(eval, tokens, pos, Some(family), name)
+                }
+                token => Err(format!(
+                    "Expected a variant after {}::, got {:?}",
+                    family, token
+                )),
+            }
+        }
+        Token::Id(ident) => match type_pattern(ident) {
+            Some(ty) => Ok(Pattern::Type(ty, None)),
+            None => Ok(Pattern::Bind(ident.clone())),
+        },
+        Token::Function(index, _) => parse_typed(eval, tokens, pos, eval.function_name(*index)),
+        Token::BlockCall(name, _) => parse_typed(eval, tokens, pos, name),
+        Token::Integer(_)
+        | Token::BigInteger(_)
+        | Token::Float(_)
+        | Token::String(_)
+        | Token::Char(_)
+        | Token::Bool(_)
+        | Token::None => Ok(Pattern::Literal(token.clone())),
+        Token::Block(Block::List(items)) => parse_list_pattern(eval, items),
+        Token::Block(Block::Literal(fields)) => parse_struct_pattern(eval, fields),
+        token => Err(format!("Invalid pattern {:?}", token)),
+    }
+}
+
+fn parse_arms(eval: &Evaluator, tokens: &[Token]) -> Result<Vec<Arm>, String> {
+    let tokens: Vec<Token> = tokens
+        .iter()
+        .filter(|token| !is_separator(token))
+        .cloned()
+        .collect();
+    let mut arms = vec![];
+    let mut pos = 0;
+    while pos < tokens.len() {
+        // `[head ..tail]: { }` is lexed as a function
+        if let Token::Block(Block::Function(inputs, body)) = &tokens[pos] {
+            arms.push(Arm {
+                pattern: parse_list_pattern(eval, inputs)?,
+                guard: None,
+                body: body.clone(),
+            });
+            pos += 1;
+            continue;
+        }
+
+        let pattern = parse_pattern(eval, &tokens, &mut pos)?;
+        let mut guard = None;
+        if let (Some(Token::Id(ident)), Some(Token::Block(Block::Literal(test)))) =
+            (tokens.get(pos), tokens.get(pos + 1))
+        {
+            if ident == "if" {
+                guard = Some(test.clone());
+                pos += 2;
+            }
+        }
+        match (tokens.get(pos), tokens.get(pos + 1)) {
+            (Some(Token::Symbol(':')), Some(Token::Block(Block::Literal(body)))) => {
+                arms.push(Arm {
+                    pattern,
+                    guard,
+                    body: body.clone(),
+                });
+                pos += 2;
+            }
+            (a, b) => return Err(format!("Expected pattern: {{ }}, got [{:?},{:?}]", a, b)),
+        }
+    }
+    Ok(arms)
+}
+
+fn matches(pattern: &Pattern, value: &Token, bindings: &mut HashMap<String, Token>) -> bool {
+    match pattern {
+        Pattern::Wildcard => true,
+        Pattern::Literal(literal) => literal.equals(value),
+        Pattern::Bind(ident) => {
+            bindings.insert(ident.clone(), value.clone());
+            true
+        }
+        Pattern::Type(ty, ident) => {
+            if value.type_name() != *ty {
+                return false;
+            }
+            if let Some(ident) = ident {
+                bindings.insert(ident.clone(), value.clone());
+            }
+            true
+        }
+        Pattern::List(patterns, rest) => {
+            let items = match value {
+                Token::Block(Block::List(items)) => items,
+                _ => return false,
+            };
+            let fits = match rest {
+                None => items.len() == patterns.len(),
+                Some(_) => items.len() >= patterns.len(),
+            };
+            if !fits
+                || !patterns
+                    .iter()
+                    .zip(items.iter())
+                    .all(|(pattern, item)| matches(pattern, item, bindings))
+            {
+                return false;
+            }
+            if let Some(Some(ident)) = rest {
+                bindings.insert(
+                    ident.clone(),
+                    Token::Block(Block::List(items[patterns.len()..].to_vec().into())),
+                );
+            }
+            true
+        }
+        Pattern::Struct(fields) => match value {
+            Token::Block(Block::Struct(data)) => fields.iter().all(|(field, pattern)| {
+                data.get(field)
+                    .is_some_and(|value| matches(pattern, value, bindings))
+            }),
+            _ => false,
+        },
+        Pattern::Record(family, name, fields) => {
+            let data = match value {
+                Token::Block(Block::Struct(data)) => data,
+                _ => return false,
+            };
+            if record::name_of(data) != Some(name.as_str())
+                || family.is_some() && record::enum_of(data) != family.as_deref()
+            {
+                return false;
+            }
+            match fields {
+                Some(patterns) => {
+                    let declared = record::fields_of(data);
+                    declared.len() == patterns.len()
+                        && declared.iter().zip(patterns).all(|(field, pattern)| {
+                            data.get(*field)
+                                .is_some_and(|value| matches(pattern, value, bindings))
+                        })
+                }
+                None => true,
+            }
+        }
+    }
+}
+
+// The arms of a match written in the source are the same list each time it
+// runs, so they are parsed the first time and kept by the list they came from
+fn cached_arms(eval: &mut Evaluator, tokens: &Instructions) -> Result<Rc<Vec<Arm>>, String> {
+    if let Some(arms) = eval.state.match_arms.get(tokens) {
+        return Ok(arms);
+    }
+    let arms = Rc::new(parse_arms(eval, tokens)?);
+    eval.state.match_arms.insert(tokens, arms.clone());
+    Ok(arms)
+}
+
+pub fn match_statement(eval: &mut Evaluator) {
+    // a range is matched as the list it makes
+    if let (Some(arms), Some(value)) = (eval.state.get_from_heap_or_pop(), iter::pop_listed(eval)) {
+        let arms = match &arms {
+            Token::Block(Block::List(tokens)) => match cached_arms(eval, tokens) {
+                Ok(arms) => arms,
+                Err(err) => {
+                    eval.state.show_error(&err);
+                    return;
+                }
+            },
+            arms => {
+                eval.state.show_error(&format!(
+                    "Incorrect arguments for match, expected a list of arms but got [{:?}]",
+                    arms
+                ));
+                return;
+            }
+        };
+
+        for arm in arms.iter() {
+            // names bound by an enclosing -> stay readable inside the arm
+            let mut bindings = eval.state.bindings.last().cloned().unwrap_or_default();
+            if !matches(&arm.pattern, &value, &mut bindings) {
+                continue;
+            }
+            eval.state.bindings.push(bindings);
+            if let Some(guard) = &arm.guard {
+                eval.evaluate(guard.clone());
+                match eval.state.get_from_heap_or_pop() {
+                    Some(Token::Bool(true)) => {}
+                    Some(Token::Bool(false)) => {
+                        eval.state.bindings.pop();
+                        continue;
+                    }
+                    other => {
+                        eval.state.bindings.pop();
+                        eval.state.show_error(&format!(
+                            "Match guard must leave a bool, got [{:?}]",
+                            other
+                        ));
+                        return;
+                    }
+                }
+            }
+            eval.evaluate(arm.body.clone());
+            eval.state.bindings.pop();
+            return;
+        }
+        eval.state
+            .show_error(&format!("No match arm for [{}]", value.to_str()));
+    } else {
+        eval.state.show_error("Not enough arguments for match");
+    }
+}
@@ -0,0 +1,93 @@
+use crate::novacore::{
+    core::{Block, Iter, Token},
+    core_ops::{control, iter},
+    evaluator::Evaluator,
+};
+
+// A struct can give an operator its own meaning with a field such as __add.
+// The function is called with both operands in order, so
+// __add = [a b]: { ... } works whichever side the struct is on
+pub fn method(value: &Token, name: &str) -> Option<Token> {
+    match value {
+        Token::Block(Block::Struct(data)) => data.get(name).cloned(),
+        _ => None,
+    }
+}
+
+fn call(eval: &mut Evaluator, name: &str, function: &Token, args: Vec<Token>) -> Option<Token> {
+    let result = control::call_value(eval, function, args);
+    if result.is_none() && eval.state.unwind.is_none() {
+        eval.state
+            .show_error(&format!("{} gave nothing back", name))
+    }
+    result
+}
+
+// Runs name for left and right if either has it and pushes what it gives.
+// False when neither does, so the operator can report its usual error
+pub fn overload(eval: &mut Evaluator, name: &str, left: &Token, right: &Token) -> bool {
+    match method(left, name).or_else(|| method(right, name)) {
+        Some(function) => {
+            if let Some(result) = call(eval, name, &function, vec![left.clone(), right.clone()]) {
+                eval.state.execution_stack.push(result)
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+// For __eq and __lt, which have to give back a bool. None if it gave
+// something else or failed
+pub fn test(
+    eval: &mut Evaluator,
+    name: &str,
+    function: &Token,
+    left: &Token,
+    right: &Token,
+) -> Option<bool> {
+    match call(eval, name, function, vec![left.clone(), right.clone()])? {
+        Token::Bool(result) => Some(result),
+        other => {
+            eval.state.show_error(&format!(
+                "{} must give back a bool, got {}",
+                name,
+                other.type_name()
+            ));
+            None
+        }
+    }
+}
+
+// A range longer than this prints as the range( that makes it instead of
+// every number in it
+const SHOWN_RANGE: i128 = 1000;
+
+// What a value prints as. A struct with __str is shown by what it gives back,
+// and a range or iterator by the list it makes
+pub fn displayed(eval: &mut Evaluator, value: Token) -> Option<Token> {
+    if let Token::Block(Block::Iter(iter)) = &value {
+        if let Iter::Range(start, end, step) = **iter {
+            if iter::range_len(start, end, step) > SHOWN_RANGE.into() {
+                let range = match step {
+                    1 => format!("range({} {})", start, end),
+                    step => format!("range({} {}) step({})", start, end, step),
+                };
+                return Some(Token::String(range));
+            }
+        }
+    }
+    match method(&value, "__str") {
+        Some(function) => match call(eval, "__str", &function, vec![value])? {
+            Token::String(text) => Some(Token::String(text)),
+            other => {
+                eval.state.show_error(&format!(
+                    "__str must give back a string, got {}",
+                    other.type_name()
+                ));
+                None
+            }
+        },
+        None => iter::listed(eval, value),
+    }
+}
@@ -0,0 +1,302 @@
+use std::rc::Rc;
+
+use fxhash::FxHashMap as HashMap;
+
+use crate::novacore::{
+    core::{Block, Operator, Token},
+    evaluator::Evaluator,
+};
+
+// A record type is a struct holding __record and __fields. Calling it makes a
+// struct with those fields and __type, so field access, with and set work on
+// records as they do on any struct
+pub fn is_type(data: &HashMap<String, Token>) -> bool {
+    data.contains_key("__record")
+}
+
+// The name of the record a struct was made by, if it was
+pub fn name_of(data: &HashMap<String, Token>) -> Option<&str> {
+    match data.get("__type") {
+        Some(Token::String(name)) => Some(name),
+        _ => None,
+    }
+}
+
+pub fn fields_of(data: &HashMap<String, Token>) -> Vec<&str> {
+    match data.get("__fields") {
+        Some(Token::Block(Block::List(fields))) => fields
+            .iter()
+            .filter_map(|field| match field {
+                Token::Id(field) => Some(field.as_str()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// A record only has the fields it was declared with
+pub fn check_field(data: &HashMap<String, Token>, field: &str) -> Result<(), String> {
+    match name_of(data) {
+        Some(name) if !fields_of(data).contains(&field) => {
+            Err(format!("Record [{}] has no field [{}]", name, field))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_fields(name: &str, fields: &[Token]) -> Result<(), String> {
+    let mut seen: Vec<&str> = vec![];
+    for field in fields {
+        match field {
+            Token::Id(field) if field.starts_with("__") => {
+                return Err(format!("Record field [{}] can not start with __", field))
+            }
+            Token::Id(field) if seen.contains(&field.as_str()) => {
+                return Err(format!("Record [{}] has the field [{}] twice", name, field))
+            }
+            Token::Id(field) => seen.push(field),
+            field => return Err(format!("Record fields must be names, got [{:?}]", field)),
+        }
+    }
+    Ok(())
+}
+
+fn record_type(name: &str, fields: Rc<Vec<Token>>) -> HashMap<String, Token> {
+    let mut record = HashMap::default();
+    record.insert("__record".to_string(), Token::String(name.to_string()));
+    record.insert("__fields".to_string(), Token::Block(Block::List(fields)));
+    record
+}
+
+// record(point [x y])
+pub fn record(eval: &mut Evaluator) {
+    if let (Some(fields), Some(name)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.execution_stack.pop(),
+    ) {
+        match (name, fields) {
+            (Token::Id(name), Token::Block(Block::List(fields))) => {
+                if let Err(err) = check_fields(&name, &fields) {
+                    eval.state.show_error(&err);
+                    return;
+                }
+                let record = record_type(&name, fields);
+                eval.state
+                    .add_varaible(&name, Token::Block(Block::Struct(Rc::new(record))))
+            }
+            (name, fields) => eval.state.show_error(&format!(
+                "Incorrect arguments for record, expected a name and a list of fields, got [{:?},{:?}]",
+                name, fields
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for record");
+    }
+}
+
+// The variants of enum(shape [circle(r) rect(w h) empty]) as names and fields
+fn read_variants(eval: &Evaluator, tokens: &[Token]) -> Result<Vec<(String, Vec<Token>)>, String> {
+    let tokens: Vec<&Token> = tokens
+        .iter()
+        .filter(|token| !matches!(token, Token::Symbol(',') | Token::Op(Operator::ArgStart, _)))
+        .collect();
+    let mut variants: Vec<(String, Vec<Token>)> = vec![];
+    let mut pos = 0;
+    while pos < tokens.len() {
+        let name = match tokens[pos] {
+            Token::Id(name) | Token::BlockCall(name, _) => name.clone(),
+            Token::Function(index, _) => eval.function_name(*index).to_string(),
+            token => return Err(format!("Expected a variant name, got [{:?}]", token)),
+        };
+        pos += 1;
+        let mut fields = vec![];
+        if let Some(Token::Symbol('(')) = tokens.get(pos) {
+            pos += 1;
+            loop {
+                match tokens.get(pos) {
+                    Some(Token::Symbol(')')) => break,
+                    Some(field) => fields.push((*field).clone()),
+                    None => return Err(format!("Missing ) after the fields of [{}]", name)),
+                }
+                pos += 1;
+            }
+            pos += 1;
+        }
+        check_fields(&name, &fields)?;
+        if variants.iter().any(|(variant, _)| *variant == name) {
+            return Err(format!("Variant [{}] is declared twice", name));
+        }
+        variants.push((name, fields));
+    }
+    Ok(variants)
+}
+
+// enum(shape [circle(r) rect(w h) empty]) makes shape a module with a record
+// type for each variant, so shape::circle(5) makes one. A variant without
+// fields is a value already, as in shape::empty
+pub fn declare_enum(eval: &mut Evaluator) {
+    if let (Some(variants), Some(name)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.execution_stack.pop(),
+    ) {
+        match (name, variants) {
+            (Token::Id(name), Token::Block(Block::List(tokens))) => {
+                let variants = match read_variants(eval, &tokens) {
+                    Ok(variants) => variants,
+                    Err(err) => {
+                        eval.state.show_error(&err);
+                        return;
+                    }
+                };
+                let mut table = HashMap::default();
+                for (variant, fields) in variants {
+                    let mut record = record_type(&variant, Rc::new(fields));
+                    record.insert("__enum".to_string(), Token::String(name.clone()));
+                    let value = if fields_of(&record).is_empty() {
+                        instance(&record, vec![])
+                    } else {
+                        record
+                    };
+                    table.insert(variant, Token::Block(Block::Struct(Rc::new(value))));
+                }
+                eval.state.modules.insert(name, table);
+            }
+            (name, variants) => eval.state.show_error(&format!(
+                "Incorrect arguments for enum, expected a name and a list of variants, got [{:?},{:?}]",
+                name, variants
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for enum");
+    }
+}
+
+// The enum a variant belongs to
+pub fn enum_of(data: &HashMap<String, Token>) -> Option<&str> {
+    match data.get("__enum") {
+        Some(Token::String(name)) => Some(name),
+        _ => None,
+    }
+}
+
+// is(shape::circle x) and is(point p) tell whether a value was made by a
+// record type or variant
+pub fn is(eval: &mut Evaluator) {
+    if let (Some(value), Some(kind)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match kind {
+            Token::Block(Block::Struct(kind)) if is_record(&kind) => {
+                let tag = match kind.get("__record") {
+                    Some(Token::String(name)) => Some(name.as_str()),
+                    _ => name_of(&kind),
+                };
+                let made = match &value {
+                    Token::Block(Block::Struct(data)) => {
+                        name_of(data) == tag && enum_of(data) == enum_of(&kind)
+                    }
+                    _ => false,
+                };
+                eval.state.execution_stack.push(Token::Bool(made))
+            }
+            kind => eval.state.show_error(&format!(
+                "Incorrect arguments for is, expected a record type or variant but got [{}]",
+                kind.to_str()
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for is");
+    }
+}
+
+// point(1 2) gives one value per field. point() and 1 2 point() take them from
+// the stack instead, like a function would
+pub fn construct(eval: &mut Evaluator, record: &HashMap<String, Token>, provided: Option<usize>) {
+    let name = match record.get("__record") {
+        Some(Token::String(name)) => name.clone(),
+        _ => return,
+    };
+    let fields = fields_of(record);
+    match provided {
+        Some(count) if count != 0 && count != fields.len() => {
+            eval.state.show_error(&format!(
+                "Record [{}] takes {} fields, got {}",
+                name,
+                fields.len(),
+                count
+            ));
+            return;
+        }
+        _ if eval.state.execution_stack.len() < fields.len() => {
+            eval.state
+                .show_error(&format!("Not enough arguments for [{}]", name));
+            return;
+        }
+        _ => {}
+    }
+    let mut values = Vec::with_capacity(fields.len());
+    for _ in 0..fields.len() {
+        match eval.state.get_from_heap_or_pop() {
+            Some(value) => values.push(value),
+            None => return,
+        }
+    }
+    values.reverse();
+    let data = instance(record, values);
+    eval.state
+        .execution_stack
+        .push(Token::Block(Block::Struct(Rc::new(data))))
+}
+
+// A value of a record type, tagged with the record's name and any enum
+fn instance(record: &HashMap<String, Token>, values: Vec<Token>) -> HashMap<String, Token> {
+    let mut data: HashMap<String, Token> = fields_of(record)
+        .iter()
+        .map(|field| field.to_string())
+        .zip(values)
+        .collect();
+    for (key, from) in [
+        ("__type", "__record"),
+        ("__fields", "__fields"),
+        ("__enum", "__enum"),
+    ] {
+        if let Some(value) = record.get(from) {
+            data.insert(key.to_string(), value.clone());
+        }
+    }
+    data
+}
+
+pub fn is_record(data: &HashMap<String, Token>) -> bool {
+    is_type(data) || name_of(data).is_some()
+}
+
+// point(x: 1, y: 2) for a value and record(point [x y]) for the type, with
+// the fields in the order they were declared. Variants show their enum, as in
+// shape::circle(r: 5)
+pub fn show(data: &HashMap<String, Token>) -> String {
+    let fields = fields_of(data);
+    let qualified = |name: &str| match enum_of(data) {
+        Some(family) => format!("{}::{}", family, name),
+        None => name.to_string(),
+    };
+    match data.get("__record") {
+        Some(Token::String(name)) => format!("record({} [{}])", qualified(name), fields.join(" ")),
+        _ => {
+            let name = qualified(name_of(data).unwrap_or("struct"));
+            if fields.is_empty() && enum_of(data).is_some() {
+                return name;
+            }
+            let values: Vec<String> = fields
+                .iter()
+                .map(|field| match data.get(*field) {
+                    Some(value) => format!("{}: {}", field, value.to_str()),
+                    None => field.to_string(),
+                })
+                .collect();
+            format!("{}({})", name, values.join(", "))
+        }
+    }
+}
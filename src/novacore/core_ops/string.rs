@@ -0,0 +1,566 @@
+use std::rc::Rc;
+
+use crate::novacore::{
+    core::{Block, Iter, Token},
+    core_ops::{iter, list},
+    evaluator::Evaluator,
+};
+
+// Positions and lengths count chars, not bytes, so "héllo" has a len of 5
+
+fn char_index(string: &str, index: i128) -> usize {
+    index.clamp(0, string.chars().count() as i128) as usize
+}
+
+fn byte_offset(string: &str, index: usize) -> usize {
+    string
+        .char_indices()
+        .nth(index)
+        .map(|(offset, _)| offset)
+        .unwrap_or(string.len())
+}
+
+// A separator, prefix or fill can be given as a string or a char
+fn as_text(token: &Token) -> Option<String> {
+    match token {
+        Token::String(string) => Some(string.clone()),
+        Token::Char(char) => Some(char.to_string()),
+        _ => None,
+    }
+}
+
+fn string_list(items: Vec<Token>) -> Token {
+    Token::Block(Block::List(Rc::new(items)))
+}
+
+pub fn split(eval: &mut Evaluator) {
+    if let (Some(separator), Some(string)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (&string, as_text(&separator)) {
+            (Token::String(string), Some(separator)) => {
+                let parts = if separator.is_empty() {
+                    string
+                        .chars()
+                        .map(|char| Token::String(char.to_string()))
+                        .collect()
+                } else {
+                    string
+                        .split(separator.as_str())
+                        .map(|part| Token::String(part.to_string()))
+                        .collect()
+                };
+                eval.state.execution_stack.push(string_list(parts))
+            }
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for split, got [{:?},{:?}]",
+                string, separator
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for split");
+    }
+}
+
+pub fn join(eval: &mut Evaluator) {
+    if let (Some(separator), Some(list)) =
+        (eval.state.get_from_heap_or_pop(), iter::pop_listed(eval))
+    {
+        match (&list, as_text(&separator)) {
+            (Token::Block(Block::List(items)), Some(separator)) => {
+                let parts: Vec<String> = items.iter().map(|item| item.to_str()).collect();
+                eval.state
+                    .execution_stack
+                    .push(Token::String(parts.join(&separator)))
+            }
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for join, got [{:?},{:?}]",
+                list, separator
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for join");
+    }
+}
+
+pub fn trim(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::String(string)) => eval
+            .state
+            .execution_stack
+            .push(Token::String(string.trim().to_string())),
+        Some(string) => eval
+            .state
+            .show_error(&format!("Incorrect arguments for trim, got [{:?}]", string)),
+        None => eval.state.show_error("Not enough arguments for trim"),
+    }
+}
+
+// Pushes the char index of the first match, or none. Given a list and a block,
+// finds the first item the block accepts
+pub fn find(eval: &mut Evaluator) {
+    if let (Some(needle), Some(string)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (&string, as_text(&needle)) {
+            (Token::String(string), Some(needle)) => {
+                let index = match string.find(needle.as_str()) {
+                    Some(offset) => Token::Integer(string[..offset].chars().count() as i128),
+                    None => Token::None,
+                };
+                eval.state.execution_stack.push(index)
+            }
+            (Token::Block(Block::List(items)), _) => {
+                list::find_item(eval, &Iter::Items(items.clone()), needle)
+            }
+            (Token::Block(Block::Iter(iter)), _) => list::find_item(eval, iter, needle),
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for find, got [{:?},{:?}]",
+                string, needle
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for find");
+    }
+}
+
+pub fn replace(eval: &mut Evaluator) {
+    if let (Some(to), Some(from), Some(string)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (&string, as_text(&from), as_text(&to)) {
+            (Token::String(string), Some(from), Some(to)) => eval
+                .state
+                .execution_stack
+                .push(Token::String(string.replace(from.as_str(), &to))),
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for replace, got [{:?},{:?},{:?}]",
+                string, from, to
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for replace");
+    }
+}
+
+// substr(string start length)
+pub fn substr(eval: &mut Evaluator) {
+    if let (Some(length), Some(start), Some(string)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (&string, &start, &length) {
+            (Token::String(string), Token::Integer(start), Token::Integer(length)) => {
+                if *start < 0 || *start > string.chars().count() as i128 {
+                    eval.state.show_error("Index out of Bounds");
+                    return;
+                }
+                let start = char_index(string, *start);
+                let end = char_index(string, start as i128 + (*length).max(0));
+                let substring = &string[byte_offset(string, start)..byte_offset(string, end)];
+                eval.state
+                    .execution_stack
+                    .push(Token::String(substring.to_string()))
+            }
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for substr, got [{:?},{:?},{:?}]",
+                string, start, length
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for substr");
+    }
+}
+
+pub fn starts_with(eval: &mut Evaluator) {
+    if let (Some(prefix), Some(string)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (&string, as_text(&prefix)) {
+            (Token::String(string), Some(prefix)) => eval
+                .state
+                .execution_stack
+                .push(Token::Bool(string.starts_with(prefix.as_str()))),
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for starts_with, got [{:?},{:?}]",
+                string, prefix
+            )),
+        }
+    } else {
+        eval.state
+            .show_error("Not enough arguments for starts_with");
+    }
+}
+
+pub fn ends_with(eval: &mut Evaluator) {
+    if let (Some(suffix), Some(string)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (&string, as_text(&suffix)) {
+            (Token::String(string), Some(suffix)) => eval
+                .state
+                .execution_stack
+                .push(Token::Bool(string.ends_with(suffix.as_str()))),
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for ends_with, got [{:?},{:?}]",
+                string, suffix
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for ends_with");
+    }
+}
+
+pub fn upper(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::String(string)) => eval
+            .state
+            .execution_stack
+            .push(Token::String(string.to_uppercase())),
+        Some(Token::Char(char)) => eval
+            .state
+            .execution_stack
+            .push(Token::String(char.to_uppercase().collect())),
+        Some(string) => eval.state.show_error(&format!(
+            "Incorrect arguments for upper, got [{:?}]",
+            string
+        )),
+        None => eval.state.show_error("Not enough arguments for upper"),
+    }
+}
+
+pub fn lower(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::String(string)) => eval
+            .state
+            .execution_stack
+            .push(Token::String(string.to_lowercase())),
+        Some(Token::Char(char)) => eval
+            .state
+            .execution_stack
+            .push(Token::String(char.to_lowercase().collect())),
+        Some(string) => eval.state.show_error(&format!(
+            "Incorrect arguments for lower, got [{:?}]",
+            string
+        )),
+        None => eval.state.show_error("Not enough arguments for lower"),
+    }
+}
+
+pub fn repeat(eval: &mut Evaluator) {
+    if let (Some(count), Some(string)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (as_text(&string), &count) {
+            (Some(string), Token::Integer(count)) if *count >= 0 => eval
+                .state
+                .execution_stack
+                .push(Token::String(string.repeat(*count as usize))),
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for repeat, got [{:?},{:?}]",
+                string, count
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for repeat");
+    }
+}
+
+fn pad(eval: &mut Evaluator, name: &str, left: bool) {
+    if let (Some(fill), Some(width), Some(string)) = (
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+        eval.state.get_from_heap_or_pop(),
+    ) {
+        match (&string, &width, as_text(&fill)) {
+            (Token::String(string), Token::Integer(width), Some(fill))
+                if fill.chars().count() == 1 =>
+            {
+                let missing = (*width - string.chars().count() as i128).max(0) as usize;
+                let padding = fill.repeat(missing);
+                let padded = if left {
+                    padding + string
+                } else {
+                    string.clone() + &padding
+                };
+                eval.state.execution_stack.push(Token::String(padded))
+            }
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for {}, expected [string width char] but got [{:?},{:?},{:?}]",
+                name, string, width, fill
+            )),
+        }
+    } else {
+        eval.state
+            .show_error(&format!("Not enough arguments for {}", name));
+    }
+}
+
+pub fn pad_left(eval: &mut Evaluator) {
+    pad(eval, "pad_left", true)
+}
+
+pub fn pad_right(eval: &mut Evaluator) {
+    pad(eval, "pad_right", false)
+}
+
+pub fn chars(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::String(string)) => eval
+            .state
+            .execution_stack
+            .push(string_list(string.chars().map(Token::Char).collect())),
+        Some(string) => eval.state.show_error(&format!(
+            "Incorrect arguments for chars, got [{:?}]",
+            string
+        )),
+        None => eval.state.show_error("Not enough arguments for chars"),
+    }
+}
+
+pub fn len(eval: &mut Evaluator) {
+    match eval.state.get_from_heap_or_pop() {
+        Some(Token::String(string)) => eval
+            .state
+            .execution_stack
+            .push(Token::Integer(string.chars().count() as i128)),
+        Some(Token::Block(Block::List(items))) => eval
+            .state
+            .execution_stack
+            .push(Token::Integer(items.len() as i128)),
+        Some(Token::Block(Block::Dict(data))) => eval
+            .state
+            .execution_stack
+            .push(Token::Integer(data.len() as i128)),
+        Some(Token::Block(Block::Iter(items))) => {
+            if let Iter::Range(start, end, step) = *items {
+                let count = iter::range_len(start, end, step);
+                eval.state.execution_stack.push(Token::from_bigint(count));
+                return;
+            }
+            let mut count = 0;
+            iter::walk(eval, &items, |_, _| {
+                count += 1;
+                true
+            });
+            if eval.state.unwind.is_none() {
+                eval.state.execution_stack.push(Token::Integer(count))
+            }
+        }
+        Some(string) => eval
+            .state
+            .show_error(&format!("Incorrect arguments for len, got [{:?}]", string)),
+        None => eval.state.show_error("Not enough arguments for len"),
+    }
+}
+
+// A placeholder spec is [[fill]align][0][width][.precision][type], where align
+// is one of < > ^, a 0 pads a number with zeros after its sign, and type is x,
+// X, b or o
+struct Spec {
+    fill: char,
+    align: Option<char>,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    kind: Option<char>,
+}
+
+fn parse_spec(spec: &str) -> Option<Spec> {
+    let chars: Vec<char> = spec.chars().collect();
+    let mut at = 0;
+    let mut parsed = Spec {
+        fill: ' ',
+        align: None,
+        zero: false,
+        width: 0,
+        precision: None,
+        kind: None,
+    };
+    if chars.len() > 1 && matches!(chars[1], '<' | '>' | '^') {
+        parsed.fill = chars[0];
+        parsed.align = Some(chars[1]);
+        at = 2;
+    } else if chars.first().is_some_and(|c| matches!(c, '<' | '>' | '^')) {
+        parsed.align = Some(chars[0]);
+        at = 1;
+    }
+    if at + 1 < chars.len() && chars[at] == '0' && chars[at + 1].is_ascii_digit() {
+        parsed.zero = true;
+        at += 1;
+    }
+    let start = at;
+    while at < chars.len() && chars[at].is_ascii_digit() {
+        at += 1;
+    }
+    if at > start {
+        parsed.width = chars[start..at].iter().collect::<String>().parse().ok()?;
+    }
+    if at < chars.len() && chars[at] == '.' {
+        at += 1;
+        let start = at;
+        while at < chars.len() && chars[at].is_ascii_digit() {
+            at += 1;
+        }
+        parsed.precision = Some(chars[start..at].iter().collect::<String>().parse().ok()?);
+    }
+    if at < chars.len() && matches!(chars[at], 'x' | 'X' | 'b' | 'o') {
+        parsed.kind = Some(chars[at]);
+        at += 1;
+    }
+    (at == chars.len()).then_some(parsed)
+}
+
+fn format_value(value: &Token, spec: &str) -> Result<String, String> {
+    let spec = parse_spec(spec).ok_or(format!("Invalid format spec [{}]", spec))?;
+    let number = matches!(
+        value,
+        Token::Integer(_) | Token::BigInteger(_) | Token::Float(_)
+    );
+    if spec.zero && !number {
+        return Err(format!(
+            "Zero fill needs a number, got [{}]",
+            value.to_str()
+        ));
+    }
+
+    let text = match (spec.kind, value.get_bigint(), value) {
+        (Some(kind), Some(int), _) => match kind {
+            'x' => format!("{:x}", int),
+            'X' => format!("{:X}", int),
+            'b' => format!("{:b}", int),
+            _ => format!("{:o}", int),
+        },
+        (Some(kind), None, value) => {
+            return Err(format!(
+                "Format type [{}] needs an integer, got [{}]",
+                kind,
+                value.to_str()
+            ))
+        }
+        (None, _, Token::Float(float)) => match spec.precision {
+            Some(precision) => format!("{:.*}", precision, float),
+            None => value.to_str(),
+        },
+        // an integer is exact, so its fraction is all zeros
+        (None, Some(int), _) => match spec.precision {
+            Some(precision) if precision > 0 => format!("{}.{}", int, "0".repeat(precision)),
+            _ => int.to_string(),
+        },
+        (None, _, Token::String(string)) => match spec.precision {
+            Some(precision) => string.chars().take(precision).collect(),
+            None => string.clone(),
+        },
+        (None, _, value) => value.to_str(),
+    };
+
+    let missing = spec.width.saturating_sub(text.chars().count());
+    if spec.zero {
+        let (sign, digits) = match text.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", text.as_str()),
+        };
+        return Ok(format!("{}{}{}", sign, "0".repeat(missing), digits));
+    }
+    let fill = |count: usize| spec.fill.to_string().repeat(count);
+    let align = spec.align.unwrap_or(if number { '>' } else { '<' });
+    Ok(match align {
+        '<' => text + &fill(missing),
+        '>' => fill(missing) + &text,
+        _ => fill(missing / 2) + &text + &fill(missing - missing / 2),
+    })
+}
+
+// Fills each {} or {:spec} in the template with the next value, {{ and }} are
+// literal braces
+fn format_template(template: &str, values: &[Token]) -> Result<String, String> {
+    let mut output = String::new();
+    let mut values = values.iter();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{')
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}')
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err("Unclosed { in format template".to_string()),
+                    }
+                }
+                let spec = match placeholder.strip_prefix(':') {
+                    Some(spec) => spec,
+                    None if placeholder.is_empty() => "",
+                    None => return Err(format!("Invalid placeholder [{{{}}}]", placeholder)),
+                };
+                let value = values
+                    .next()
+                    .ok_or("Not enough values for format template")?;
+                output.push_str(&format_value(value, spec)?)
+            }
+            '}' => return Err("Unmatched } in format template".to_string()),
+            _ => output.push(c),
+        }
+    }
+    Ok(output)
+}
+
+// format(template values) takes its values as a list or as a block, whose
+// results are used in order
+pub fn format(eval: &mut Evaluator) {
+    if let (Some(values), Some(template)) =
+        (iter::pop_listed(eval), eval.state.get_from_heap_or_pop())
+    {
+        let values: Vec<Token> = match values {
+            Token::Block(Block::List(list)) => list.iter().cloned().collect(),
+            Token::Block(Block::Literal(block)) => {
+                let start = eval.state.execution_stack.len();
+                eval.evaluate(block);
+                eval.state.execution_stack.split_off(start)
+            }
+            values => {
+                eval.state.show_error(&format!(
+                    "Incorrect arguments for format, got [{:?},{:?}]",
+                    template, values
+                ));
+                return;
+            }
+        };
+        let values: Vec<Token> = values
+            .into_iter()
+            .map(|value| match &value {
+                Token::Id(ident) => eval.state.get_from_heap(ident).unwrap_or(value),
+                _ => value,
+            })
+            .collect();
+
+        match &template {
+            Token::String(template) => match format_template(template, &values) {
+                Ok(string) => eval.state.execution_stack.push(Token::String(string)),
+                Err(err) => eval.state.show_error(&err),
+            },
+            _ => eval.state.show_error(&format!(
+                "Incorrect arguments for format, got [{:?},{:?}]",
+                template, values
+            )),
+        }
+    } else {
+        eval.state.show_error("Not enough arguments for format");
+    }
+}
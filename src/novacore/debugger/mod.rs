@@ -35,7 +35,7 @@ pub fn debug_output(depth: usize, block: Rc<Vec<Token>>) {
                     debug_output(depth + 1, block.clone());
                     continue;
                 }
-                Block::Function(_inputs, block) => {
+                Block::Function(_inputs, block) | Block::Method(_, _inputs, block) => {
                     println!(
                         "{}{}{}",
                         sdep.bright_cyan(),
@@ -64,6 +64,24 @@ pub fn debug_output(depth: usize, block: Rc<Vec<Token>>) {
                     );
                     continue;
                 }
+                Block::Dict(_) => {
+                    println!(
+                        "{}{}{}",
+                        sdep.bright_cyan(),
+                        "|--".bright_cyan(),
+                        "Dict:".bright_cyan()
+                    );
+                    continue;
+                }
+                Block::Iter(_) => {
+                    println!(
+                        "{}{}{}",
+                        sdep.bright_cyan(),
+                        "|--".bright_cyan(),
+                        "Iter:".bright_cyan()
+                    );
+                    continue;
+                }
             }
         }
         println!("{}[{}]", sdep.bright_cyan(), t.to_str_debug().bright_blue());
@@ -19,6 +19,10 @@ impl Evaluator {
         self.functions.len() - 1
     }
 
+    pub fn function_name(&self, index: usize) -> &str {
+        &self.functions[index].1
+    }
+
     pub fn eval(&mut self, expr: Token) {
         match expr {
             Token::Reg(opcodes, main) => core_ops::reg::register_operation(self, opcodes, main),
@@ -186,12 +190,14 @@ impl Evaluator {
 
                 match operator {
                     Operator::BindVar => core_ops::operator::bind_variables(self),
+                    Operator::ArgStart => self.state.mark_arguments(),
                     Operator::ResolveBind => core_ops::operator::resolve_binding(self),
                     Operator::PopBindings => core_ops::operator::pop_bindings(self),
                     Operator::Break => core_ops::control::break_loop(self),
                     Operator::Continue => core_ops::control::continue_loop(self),
                     Operator::Neg => core_ops::operator::neg(self),
                     Operator::AccessCall => core_ops::control::get_access(self),
+                    Operator::AccessPath => core_ops::control::access_path(self),
                     Operator::UserFunctionChain => core_ops::control::user_chain_call(self),
                     Operator::StoreTemp => core_ops::control::store_temp(self),
                     Operator::And => core_ops::logical::logical_and(self),
@@ -200,6 +206,13 @@ impl Evaluator {
                     Operator::Equals => core_ops::comparison::equality_comparison(self),
                     Operator::Gtr => core_ops::comparison::greater_than_comparison(self),
                     Operator::Lss => core_ops::comparison::less_than_comparison(self),
+                    Operator::NotEquals => core_ops::comparison::inequality_comparison(self),
+                    Operator::GtrEq => core_ops::comparison::greater_equal_comparison(self),
+                    Operator::LssEq => core_ops::comparison::less_equal_comparison(self),
+                    Operator::BitAnd => core_ops::bitwise::bit_and(self),
+                    Operator::BitOr => core_ops::bitwise::bit_or(self),
+                    Operator::Shl => core_ops::bitwise::shift_left(self),
+                    Operator::Shr => core_ops::bitwise::shift_right(self),
                     Operator::Invert => core_ops::operator::neg(self),
                     Operator::Mod => core_ops::operator::modulo(self),
                     Operator::Add => core_ops::operator::add(self),
@@ -207,8 +220,10 @@ impl Evaluator {
                     Operator::Mul => core_ops::operator::mul(self),
                     Operator::Div => core_ops::operator::div(self),
                     Operator::VariableAssign => core_ops::operator::variable_assign(self),
+                    Operator::ConstAssign => core_ops::operator::const_assign(self),
                     Operator::New => core_ops::operator::get_new(self),
                     Operator::ModuleCall => core_ops::control::module(self),
+                    Operator::Infix(op) => core_ops::operator::user_operator(self, op),
                 }
                 if self.debug {
                     self.state.traceback.pop();
@@ -241,6 +256,10 @@ impl Evaluator {
     pub fn evaluate(&mut self, expr: Rc<Vec<Token>>) {
         for t in &*expr {
             self.eval(t.clone());
+            // a break or continue is on its way out to a loop
+            if self.state.unwind.is_some() {
+                break;
+            }
             // if let Some(last) = self.get_stack_output() {
             //     println!(" ---> {}", last)
             // }
@@ -248,11 +267,14 @@ impl Evaluator {
     }
 
     pub fn evaluate_function(&mut self, expr: Rc<Vec<Token>>) {
-        self.state.call_stack.push(HashMap::default());
+        self.state.push_scope(HashMap::default());
         for t in &*expr {
             self.eval(t.clone());
+            if self.state.unwind.is_some() {
+                break;
+            }
         }
-        self.state.call_stack.pop();
+        self.state.pop_scope();
     }
 }
 
@@ -1,11 +1,12 @@
-use super::{super::novacore};
-use crate::novacore::{utilities::print_line};
+use super::super::novacore;
+use crate::novacore::utilities::print_line;
 use colored::Colorize;
 use fxhash::FxHashMap as HashMap;
+use num_bigint::BigInt;
 use std::{rc::Rc, vec};
 
 use super::{
-    core::{Block, Operator, Token},
+    core::{Block, DictMap, Operator, Token, UserOp},
     utilities::is_string_number,
 };
 
@@ -20,7 +21,8 @@ pub struct Lexer {
     is_parsing_stringdq: bool,
     is_parsing_stringsq: bool,
     is_parsing_comment: bool,
-    is_skip: bool,
+    // the chars read so far after a \ in a string or char
+    escape: Option<String>,
 
     // Output
     tokens: Vec<Vec<Token>>,
@@ -35,6 +37,18 @@ pub struct Lexer {
     stringpair: Vec<usize>,
     charpair: Vec<usize>,
     bindpair: Vec<usize>,
+
+    // paren depth of a set( whose first argument is an assignment path
+    set_path: Option<usize>,
+
+    // "pos {x}, {y:.2}" is lexed into the text before each expression, the
+    // expression and its format spec, with the text after it in token_buffer
+    interpolated: Vec<(String, String, Option<String>)>,
+
+    // operators declared with infix(, longest symbol first, and where the
+    // text of the last declaration or operator read ends
+    operators: Vec<Rc<UserOp>>,
+    skip_to: usize,
 }
 
 pub fn new() -> Lexer {
@@ -45,7 +59,7 @@ pub fn new() -> Lexer {
         is_parsing_stringsq: false,
         tokens: vec![vec![]],
         is_parsing_comment: false,
-        is_skip: false,
+        escape: None,
         function_list: HashMap::default(),
         line: 1,
         _col: 1,
@@ -56,9 +70,289 @@ pub fn new() -> Lexer {
         stringpair: vec![],
         bindpair: vec![],
         charpair: vec![],
+        set_path: None,
+        interpolated: vec![],
+        operators: vec![],
+        skip_to: 0,
     }
 }
 
+// Characters a declared operator can be made of
+const OPERATOR_CHARS: &str = "+-*/<>=!&|?@%^~";
+
+// Operators that already mean something, which infix can not take over. @
+// marks labels in register blocks
+const BUILTIN_OPERATORS: [&str; 21] = [
+    "+", "-", "*", "/", "%", "<", ">", "<=", ">=", "==", "!=", "=", "!", "&", "|", "^", "~", "<<",
+    ">>", "->", "@",
+];
+
+// infix(<> 9 left concat) declares <> with precedence 9. + and - are 13 and
+// * and / are 14, comparisons are 9 and = is 2
+fn read_infix(declaration: &str) -> Result<UserOp, String> {
+    let usage = "infix expects a symbol, a precedence, left or right and a function, as in infix(<> 9 left concat)";
+    let parts: Vec<&str> = declaration.split_whitespace().collect();
+    let (symbol, precedence, associativity, function) = match parts.as_slice() {
+        [symbol, precedence, associativity, function] => {
+            (*symbol, *precedence, *associativity, *function)
+        }
+        _ => return Err(usage.to_string()),
+    };
+    if !symbol.chars().all(|c| OPERATOR_CHARS.contains(c)) {
+        return Err(format!(
+            "Operator [{}] can only be made of {}",
+            symbol, OPERATOR_CHARS
+        ));
+    }
+    if BUILTIN_OPERATORS.contains(&symbol) {
+        return Err(format!("Operator [{}] is already defined", symbol));
+    }
+    let precedence = match precedence.parse::<usize>() {
+        Ok(precedence) if (3..=16).contains(&precedence) => precedence,
+        _ => {
+            return Err(format!(
+                "Precedence of [{}] must be a number from 3 to 16, got {}",
+                symbol, precedence
+            ))
+        }
+    };
+    let left = match associativity {
+        "left" => true,
+        "right" => false,
+        _ => return Err(format!("Expected left or right, got {}", associativity)),
+    };
+    if !function.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        || !function
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(format!("Expected a function name, got {}", function));
+    }
+    Ok(UserOp {
+        symbol: symbol.to_string(),
+        precedence,
+        left,
+        function: function.to_string(),
+    })
+}
+
+// Numbers can be written as 255, -255, 0xFF, 0b1111_1111, 0o377, 2.5, 1.5e-3,
+// -inf or nan, with _ allowed between digits
+fn number_literal(text: &str) -> Option<Token> {
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(body) => (true, body),
+        None => (false, text),
+    };
+    let sign = if negative { -1.0 } else { 1.0 };
+    match body {
+        "inf" if negative => return Some(Token::Float(f64::NEG_INFINITY)),
+        "nan" if negative => return Some(Token::Float(f64::NAN)),
+        _ => {}
+    }
+    if !body.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let radix = match body.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0b" | "0B") => 2,
+        Some("0o" | "0O") => 8,
+        _ => 10,
+    };
+    let digits: String = if radix == 10 { body } else { &body[2..] }
+        .chars()
+        .filter(|c| *c != '_')
+        .collect();
+    if digits.is_empty() {
+        return None;
+    }
+
+    if digits.chars().all(|c| c.is_digit(radix)) {
+        let value = BigInt::parse_bytes(digits.as_bytes(), radix)?;
+        Some(Token::from_bigint(if negative { -value } else { value }))
+    } else if radix == 10
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+    {
+        digits
+            .parse::<f64>()
+            .ok()
+            .map(|float| Token::Float(sign * float))
+    } else {
+        None
+    }
+}
+
+// The - or + in 1.5e-3 belongs to the number
+fn is_exponent_start(buffer: &str) -> bool {
+    let body = buffer.trim_start_matches('-');
+    body.starts_with(|c: char| c.is_ascii_digit())
+        && !body.starts_with("0x")
+        && !body.starts_with("0X")
+        && (body.ends_with('e') || body.ends_with('E'))
+}
+
+// Ok(None) means the escape needs more chars
+fn escape_sequence(escape: &str) -> Result<Option<char>, String> {
+    let mut chars = escape.chars();
+    let kind = match chars.next() {
+        Some(kind) => kind,
+        None => return Ok(None),
+    };
+    let rest = chars.as_str();
+    match kind {
+        'n' => Ok(Some('\n')),
+        't' => Ok(Some('\t')),
+        'r' => Ok(Some('\r')),
+        '0' => Ok(Some('\0')),
+        '\\' | '"' | '\'' | '{' | '}' => Ok(Some(kind)),
+        'x' => {
+            if !rest.chars().all(|c| c.is_ascii_hexdigit()) {
+                Err(format!(
+                    "Invalid escape \\{}, expected two hex digits",
+                    escape
+                ))
+            } else if rest.len() < 2 {
+                Ok(None)
+            } else {
+                match u8::from_str_radix(rest, 16) {
+                    Ok(byte) if byte <= 0x7f => Ok(Some(byte as char)),
+                    _ => Err(format!(
+                        "Invalid escape \\{}, \\x can only go up to \\x7F",
+                        escape
+                    )),
+                }
+            }
+        }
+        'u' => {
+            if rest.is_empty() {
+                return Ok(None);
+            }
+            let digits = match rest.strip_prefix('{') {
+                Some(digits) => digits,
+                None => return Err(format!("Invalid escape \\{}, expected \\u{{...}}", escape)),
+            };
+            match digits.strip_suffix('}') {
+                Some(digits) if !digits.is_empty() => u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map(Some)
+                    .ok_or(format!(
+                        "Invalid escape \\{}, not a unicode code point",
+                        escape
+                    )),
+                None if digits.len() <= 6 && digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                    Ok(None)
+                }
+                _ => Err(format!(
+                    "Invalid escape \\{}, expected 1 to 6 hex digits in \\u{{...}}",
+                    escape
+                )),
+            }
+        }
+        _ => Err(format!("Unknown escape sequence \\{}", kind)),
+    }
+}
+
+// Where the } that closes the { at open is, skipping over strings and chars
+// inside it. None if the line ends first, so the { is just a brace
+fn interpolation_end(chars: &[char], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, &c) in chars.iter().enumerate().skip(open + 1) {
+        match (quote, c) {
+            (_, '\n') => return None,
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') if depth == 0 => return Some(index),
+            (None, '}') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+// Splits "y:.2" into the expression and its format spec. A colon only
+// starts a spec if what follows is one, so y::z and [a]: { } are left alone.
+fn split_format_spec(expression: &str) -> (String, Option<String>) {
+    if let Some(at) = expression.rfind(':') {
+        let (before, spec) = (&expression[..at], &expression[at + 1..]);
+        if !before.ends_with(':') && !spec.is_empty() && is_format_spec(spec) {
+            return (before.to_string(), Some(spec.to_string()));
+        }
+    }
+    (expression.to_string(), None)
+}
+
+fn is_format_spec(spec: &str) -> bool {
+    let mut chars = spec.chars().peekable();
+    // optional fill and alignment
+    let mut lookahead = spec.chars().skip(1);
+    if matches!(lookahead.next(), Some('<' | '>' | '^')) {
+        chars.next();
+        chars.next();
+    } else if matches!(chars.peek(), Some('<' | '>' | '^')) {
+        chars.next();
+    }
+    while chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+        chars.next();
+    }
+    if chars.peek() == Some(&'.') {
+        chars.next();
+        if !chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+            return false;
+        }
+        while chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+            chars.next();
+        }
+    }
+    if matches!(chars.peek(), Some('x' | 'X' | 'b' | 'o')) {
+        chars.next();
+    }
+    chars.next().is_none()
+}
+
+// %[key: value] as pairs. A key is the one token before its ':' and its value
+// runs up to the next key, so a value can be a whole expression
+fn dict_literal(list: Vec<Token>) -> Result<Vec<(Token, Vec<Token>)>, String> {
+    let list: Vec<Token> = list
+        .into_iter()
+        .filter(|token| *token != Token::Symbol(','))
+        .collect();
+    let colons: Vec<usize> = list
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| **token == Token::Symbol(':'))
+        .map(|(index, _)| index)
+        .collect();
+    match (list.first(), colons.first()) {
+        (None, _) => return Ok(vec![]),
+        (Some(_), Some(1)) => {}
+        (Some(key), _) => return Err(format!("Expected key: value in dict, got {:?}", key)),
+    }
+    let mut pairs = vec![];
+    for (nth, &colon) in colons.iter().enumerate() {
+        let end = match colons.get(nth + 1) {
+            Some(next) => next - 1,
+            None => list.len(),
+        };
+        if end <= colon + 1 {
+            return Err(format!(
+                "Missing value for key {:?} in dict",
+                list[colon - 1]
+            ));
+        }
+        pairs.push((list[colon - 1].clone(), list[colon + 1..end].to_vec()));
+    }
+    Ok(pairs)
+}
+
 impl Lexer {
     pub fn get_function_list(&self) -> HashMap<String, usize> {
         self.function_list.clone()
@@ -81,6 +375,58 @@ impl Lexer {
         self.file += input
     }
 
+    // The repl lexes each line on its own, so declared operators are carried over
+    pub fn take_operators(&mut self) -> Vec<Rc<UserOp>> {
+        std::mem::take(&mut self.operators)
+    }
+
+    pub fn set_operators(&mut self, operators: Vec<Rc<UserOp>>) {
+        self.operators = operators
+    }
+
+    // Reads infix( up to its ) and skips it, so it leaves no code behind
+    fn declare_infix(&mut self, chars: &[char], open: usize) {
+        let close = match chars[open..].iter().position(|&c| c == ')') {
+            Some(close) => open + close,
+            None => {
+                println!();
+                println!("{}: Missing ) after infix(", "LEXING ERROR".red());
+                print_line(self.line, &self.filename);
+                std::process::exit(1)
+            }
+        };
+        let declaration: String = chars[open + 1..close].iter().collect();
+        match read_infix(&declaration) {
+            Ok(op) => {
+                self.operators
+                    .retain(|declared| declared.symbol != op.symbol);
+                self.operators.push(Rc::new(op));
+                self.operators
+                    .sort_by_key(|op| std::cmp::Reverse(op.symbol.chars().count()));
+            }
+            Err(err) => {
+                println!();
+                println!("{}: {}", "LEXING ERROR".red(), err);
+                print_line(self.line, &self.filename);
+                std::process::exit(1)
+            }
+        }
+        self.line += declaration.matches('\n').count();
+        self.token_buffer.clear();
+        self.skip_to = close + 1;
+    }
+
+    // The declared operator starting at chars, if there is one
+    fn user_operator(&self, chars: &[char]) -> Option<Rc<UserOp>> {
+        self.operators
+            .iter()
+            .find(|op| {
+                let mut rest = chars.iter();
+                op.symbol.chars().all(|c| rest.next() == Some(&c))
+            })
+            .cloned()
+    }
+
     pub fn add_function(&mut self, name: &str, index: usize) {
         self.function_list.insert(name.to_string(), index);
     }
@@ -94,13 +440,17 @@ impl Lexer {
 
             "true" => Token::Bool(true),
             "false" => Token::Bool(false),
+            "none" => Token::None,
 
             "and" => Token::Op(Operator::And, self.line),
             "or" => Token::Op(Operator::Or, self.line),
 
             "-" => Token::Op(Operator::Sub, self.line),
+
+            "inf" => Token::Float(f64::INFINITY),
+            "nan" => Token::Float(f64::NAN),
             _ => {
-                if token.contains('.') {
+                if token.contains('.') && !token.starts_with("..") {
                     println!();
                     println!("{}: Is not a valid FLoat", "LEXING ERROR".red());
                     std::process::exit(1)
@@ -118,18 +468,21 @@ impl Lexer {
     // // buffer is either a (number,Op,bool,identifier)
     fn check_token_buffer(&self) -> Option<Token> {
         if !self.token_buffer.is_empty() {
-            if is_string_number(&self.token_buffer) {
-                // Float
-                if self.token_buffer.contains('.') {
-                    if let Ok(v) = self.token_buffer.parse() {
-                        return Some(Token::Float(v));
-                    }
-                } else {
-                    // Int
-                    if let Ok(v) = self.token_buffer.parse() {
-                        return Some(Token::Integer(v));
-                    }
-                }
+            if let Some(number) = number_literal(&self.token_buffer) {
+                return Some(number);
+            } else if self
+                .token_buffer
+                .trim_start_matches('-')
+                .starts_with(|c: char| c.is_ascii_digit())
+            {
+                println!();
+                println!(
+                    "{}: [{}] is not a valid number",
+                    "LEXING ERROR".red(),
+                    self.token_buffer
+                );
+                print_line(self.line, &self.filename);
+                std::process::exit(1)
             } else {
                 return Some(self.match_token(&self.token_buffer.to_lowercase()));
             }
@@ -152,6 +505,108 @@ impl Lexer {
         }
     }
 
+    // The target of set( ends with its first argument
+    fn close_set_path(&mut self) {
+        if self.set_path == Some(self.paren.len()) {
+            if let Some(Token::Symbol('(')) = self.last_token() {
+                return;
+            }
+            self.set_path = None;
+        }
+    }
+
+    // Fed each char after a \ until the escape is complete
+    fn read_escape(&mut self, c: char) -> Option<char> {
+        let mut escape = self.escape.take().unwrap_or_default();
+        escape.push(c);
+        match escape_sequence(&escape) {
+            Ok(Some(c)) => Some(c),
+            Ok(None) => {
+                self.escape = Some(escape);
+                None
+            }
+            Err(err) => {
+                println!();
+                println!("{}: {}", "LEXING ERROR".red(), err);
+                print_line(self.line, &self.filename);
+                std::process::exit(1)
+            }
+        }
+    }
+
+    // An expression inside a string, lexed on its own
+    fn lex_expression(&self, expression: &str) -> Vec<Token> {
+        let mut lexer = new();
+        lexer.function_list = self.function_list.clone();
+        lexer.operators = self.operators.clone();
+        lexer.filename = self.filename.clone();
+        lexer.line = self.line;
+        lexer.insert_string(expression);
+        match lexer.parse() {
+            Ok(tokens) => tokens,
+            Err(err) => {
+                println!();
+                println!("{}: {}", "LEXING ERROR".red(), err);
+                print_line(self.line, &self.filename);
+                std::process::exit(1)
+            }
+        }
+    }
+
+    // "a {x} b {y:.2}" becomes ("a " + str(x) + " b " + format("{:.2}" { y }))
+    fn add_interpolation(&mut self) {
+        let line = self.line;
+        let function = |name: &str| {
+            self.function_list
+                .get(name)
+                .map(|&index| Token::Function(index, line))
+        };
+        let (to_str, format) = match (function("str"), function("format")) {
+            (Some(to_str), Some(format)) => (to_str, format),
+            _ => {
+                println!();
+                println!(
+                    "{}: String interpolation needs the str and format functions",
+                    "LEXING ERROR".red()
+                );
+                print_line(self.line, &self.filename);
+                std::process::exit(1)
+            }
+        };
+        let mut tokens = vec![Token::Symbol('(')];
+        for (text, expression, spec) in std::mem::take(&mut self.interpolated) {
+            if !text.is_empty() || tokens.len() == 1 {
+                tokens.push(Token::String(text));
+                tokens.push(Token::Op(Operator::Add, line));
+            }
+            let expression = self.lex_expression(&expression);
+            match spec {
+                Some(spec) => {
+                    tokens.push(format.clone());
+                    tokens.push(Token::Symbol('('));
+                    tokens.push(Token::String(format!("{{:{}}}", spec)));
+                    tokens.push(Token::Block(Block::Literal(Rc::new(expression))));
+                }
+                None => {
+                    tokens.push(to_str.clone());
+                    tokens.push(Token::Symbol('('));
+                    tokens.extend(expression);
+                }
+            }
+            tokens.push(Token::Symbol(')'));
+            tokens.push(Token::Op(Operator::Add, line));
+        }
+        if self.token_buffer.is_empty() {
+            tokens.pop();
+        } else {
+            tokens.push(Token::String(std::mem::take(&mut self.token_buffer)));
+        }
+        tokens.push(Token::Symbol(')'));
+        if let Some(vec_last) = self.tokens.last_mut() {
+            vec_last.extend(tokens);
+        }
+    }
+
     fn last_token(&self) -> Option<&Token> {
         if let Some(vec_last) = self.tokens.last() {
             vec_last.last()
@@ -160,19 +615,37 @@ impl Lexer {
         }
     }
 
+    // [x y]:int right before a block is a function that says what it gives back
+    fn ends_with_return_type(&self) -> bool {
+        match self.tokens.last().map(|vec_last| vec_last.as_slice()) {
+            Some([.., Token::Block(Block::List(_)), Token::Symbol(':'), ty]) => {
+                matches!(ty, Token::Id(_) | Token::Function(..) | Token::None)
+            }
+            _ => false,
+        }
+    }
+
     // // Going through each char in the file or string
     pub fn parse(&mut self) -> Result<Vec<Token>, &str> {
-        for c in self.file.clone().chars() {
+        let chars: Vec<char> = self.file.chars().collect();
+        self.skip_to = 0;
+        for (index, &c) in chars.iter().enumerate() {
+            if index < self.skip_to {
+                continue;
+            }
             if self.is_parsing_stringsq {
+                if self.escape.is_some() {
+                    if let Some(c) = self.read_escape(c) {
+                        self.token_buffer.push(c);
+                    }
+                    continue;
+                }
                 if c == '\\' {
-                    self.is_skip = true;
+                    self.escape = Some(String::new());
                     continue;
                 }
-                if c != '\'' || self.is_skip {
+                if c != '\'' {
                     self.token_buffer.push(c);
-                    if self.is_skip {
-                        self.is_skip = false;
-                    }
                     continue;
                 } else {
                     self.charpair.pop();
@@ -197,20 +670,52 @@ impl Lexer {
             }
 
             if self.is_parsing_stringdq {
+                if self.escape.is_some() {
+                    if let Some(c) = self.read_escape(c) {
+                        self.token_buffer.push(c);
+                    }
+                    continue;
+                }
                 if c == '\\' {
-                    self.is_skip = true;
+                    self.escape = Some(String::new());
                     continue;
                 }
-                if c != '"' || self.is_skip {
+                // {{ and }} are a brace, as is one without a partner
+                if matches!(c, '{' | '}') && chars.get(index + 1) == Some(&c) {
                     self.token_buffer.push(c);
-                    if self.is_skip {
-                        self.is_skip = false;
+                    self.skip_to = index + 2;
+                    continue;
+                }
+                if c == '{' {
+                    if let Some(close) = interpolation_end(&chars, index) {
+                        let expression: String = chars[index + 1..close].iter().collect();
+                        let (expression, spec) = split_format_spec(&expression);
+                        self.skip_to = close + 1;
+                        // {} and {:spec} with nothing to fill them are left
+                        // in place for format
+                        if expression.trim().is_empty() {
+                            self.token_buffer.push('{');
+                            if let Some(spec) = spec {
+                                self.token_buffer.push(':');
+                                self.token_buffer.push_str(&spec);
+                            }
+                            self.token_buffer.push('}');
+                        } else {
+                            let text = std::mem::take(&mut self.token_buffer);
+                            self.interpolated.push((text, expression, spec));
+                        }
+                        continue;
                     }
+                }
+                if c != '"' {
+                    self.token_buffer.push(c);
                     continue;
                 } else {
                     self.stringpair.pop();
                     self.is_parsing_stringdq = false;
-                    if let Some(vec_last) = self.tokens.last_mut() {
+                    if !self.interpolated.is_empty() {
+                        self.add_interpolation();
+                    } else if let Some(vec_last) = self.tokens.last_mut() {
                         // if self.token_buffer.chars().count() == 1 {
                         //     if let Some(mychar) = self.token_buffer.chars().next() {
                         //         vec_last.push(Token::Char(mychar))
@@ -236,12 +741,26 @@ impl Lexer {
                 }
             }
 
+            if c == '(' && self.token_buffer.eq_ignore_ascii_case("infix") {
+                self.declare_infix(&chars, index);
+                continue;
+            }
+            if OPERATOR_CHARS.contains(c) {
+                if let Some(op) = self.user_operator(&chars[index..]) {
+                    self.check_token();
+                    self.skip_to = index + op.symbol.chars().count();
+                    self.add_token(Token::Op(Operator::Infix(op), self.line));
+                    continue;
+                }
+            }
+
             // Main parsing Op going through each char and adding them to a buffer
             // if no match is found
             match c {
                 // Newline
                 '\n' => {
                     self.check_token();
+                    self.close_set_path();
                     self.add_token(Token::Symbol(','));
                     self.line += 1;
                     continue;
@@ -254,36 +773,84 @@ impl Lexer {
                 }
 
                 // Letters and numbers
-                'a'..='z' | 'A'..='Z' | '_' | '0'..='9' | '-' => {
+                'a'..='z' | 'A'..='Z' | '_' | '0'..='9' => {
                     self.token_buffer.push(c);
                 }
 
+                // Right after a value, as in x-1 or f(x)-1, - subtracts. Anywhere
+                // else it may start a negative literal such as -5
+                '-' => {
+                    let previous = index.checked_sub(1).map(|index| chars[index]);
+                    let starts_literal = self.token_buffer.is_empty()
+                        && !matches!(previous, Some(')' | ']' | '"' | '\''));
+                    if starts_literal || is_exponent_start(&self.token_buffer) {
+                        self.token_buffer.push(c);
+                    } else {
+                        self.check_token();
+                        self.add_token(Token::Op(Operator::Sub, self.line));
+                    }
+                }
+
                 // Spaces
                 ' ' => {
                     self.check_token();
+                    self.close_set_path();
                 }
 
                 '.' => {
-                    if is_string_number(&self.token_buffer) && !(&self.token_buffer.contains('.')) {
+                    // an index such as the 1 in l.1.0 is never a float
+                    let is_index = matches!(
+                        self.last_token(),
+                        Some(Token::Op(Operator::AccessCall | Operator::AccessPath, _))
+                    );
+                    if is_string_number(&self.token_buffer.replace('_', ""))
+                        && !(&self.token_buffer.contains('.'))
+                        && !is_index
+                    {
                         self.token_buffer.push(c);
                         continue;
                     }
 
+                    // `..rest` collects the remainder of a list
+                    if self.token_buffer.is_empty() {
+                        if let Some(Token::Op(Operator::AccessCall, _)) = self.last_token() {
+                            if let Some(vec_last) = self.tokens.last_mut() {
+                                vec_last.pop();
+                            }
+                            self.token_buffer.push_str("..");
+                            continue;
+                        }
+                    }
+
+                    let access = if self.set_path == Some(self.paren.len()) {
+                        Operator::AccessPath
+                    } else {
+                        Operator::AccessCall
+                    };
                     if let Some(t) = self.check_token_buffer() {
                         if let Some(vec_last) = self.tokens.last_mut() {
                             vec_last.push(t);
-                            vec_last.push(Token::Op(Operator::AccessCall, self.line))
+                            vec_last.push(Token::Op(access, self.line))
                         }
                         self.token_buffer.clear();
                     } else if let Some(vec_last) = self.tokens.last_mut() {
-                        vec_last.push(Token::Op(Operator::AccessCall, self.line))
+                        vec_last.push(Token::Op(access, self.line))
                     }
                 }
 
                 // Symbols
                 '+' | '*' | '/' | '(' | ')' | '<' | '>' | '`' | '~' | '@' | '%' | '^' | '&'
                 | ',' | '?' | ';' | ':' | '=' | '!' | '$' | '|' => {
+                    if c == '+' && is_exponent_start(&self.token_buffer) {
+                        self.token_buffer.push(c);
+                        continue;
+                    }
                     self.check_token();
+                    match c {
+                        ',' => self.close_set_path(),
+                        ')' if self.set_path == Some(self.paren.len()) => self.set_path = None,
+                        _ => {}
+                    }
 
                     if let Some(vec_last) = self.tokens.last_mut() {
                         match c {
@@ -347,6 +914,25 @@ impl Lexer {
                                     match &last {
                                         Token::Id(ident) => {
                                             if let Some(index) = self.function_list.get(ident) {
+                                                if ident == "set" {
+                                                    self.set_path = Some(self.paren.len());
+                                                }
+                                                // modules can't define builtin names, so
+                                                // std::map( is the builtin map(
+                                                if let Some(Token::Op(Operator::ModuleCall, _)) =
+                                                    vec_last.last()
+                                                {
+                                                    vec_last.pop();
+                                                    vec_last.pop();
+                                                }
+                                                // call and exe pass on how many
+                                                // arguments they were given
+                                                if ident == "call" || ident == "exe" {
+                                                    vec_last.push(Token::Op(
+                                                        Operator::ArgStart,
+                                                        self.line,
+                                                    ));
+                                                }
                                                 vec_last.push(Token::Function(*index, self.line));
                                                 vec_last.push(Token::Symbol(c));
                                                 continue;
@@ -377,6 +963,8 @@ impl Lexer {
                                                     continue;
                                                 }
 
+                                                vec_last
+                                                    .push(Token::Op(Operator::ArgStart, self.line));
                                                 vec_last.push(Token::BlockCall(
                                                     ident.clone(),
                                                     self.line,
@@ -404,6 +992,22 @@ impl Lexer {
                                             vec_last.push(Token::Symbol(c));
                                             continue;
                                         }
+                                        // break(label) and continue(label) take arguments
+                                        Token::Op(Operator::Break, line)
+                                        | Token::Op(Operator::Continue, line) => {
+                                            let name = if let Token::Op(Operator::Break, _) = last {
+                                                "break"
+                                            } else {
+                                                "continue"
+                                            };
+                                            if let Some(index) = self.function_list.get(name) {
+                                                vec_last.push(Token::Function(*index, *line));
+                                            } else {
+                                                vec_last.push(last.clone());
+                                            }
+                                            vec_last.push(Token::Symbol(c));
+                                            continue;
+                                        }
                                         Token::Block(Block::Literal(block)) => {
                                             vec_last
                                                 .push(Token::Block(Block::Lambda(block.clone())));
@@ -435,7 +1039,14 @@ impl Lexer {
                                     vec_last.push(Token::Symbol(c))
                                 }
                             }
-                            '<' => self.add_token(Token::Op(Operator::Lss, self.line)),
+                            '<' => {
+                                if let Some(Token::Op(Operator::Lss, _)) = vec_last.last() {
+                                    vec_last.pop();
+                                    vec_last.push(Token::Op(Operator::Shl, self.line))
+                                } else {
+                                    vec_last.push(Token::Op(Operator::Lss, self.line))
+                                }
+                            }
                             '>' => {
                                 if let Some(last) = vec_last.pop() {
                                     match last {
@@ -456,6 +1067,9 @@ impl Lexer {
                                                 std::process::exit(1)
                                             }
                                         }
+                                        Token::Op(Operator::Gtr, _) => {
+                                            vec_last.push(Token::Op(Operator::Shr, self.line))
+                                        }
                                         _ => {
                                             vec_last.push(last);
                                             vec_last.push(Token::Op(Operator::Gtr, self.line))
@@ -472,16 +1086,27 @@ impl Lexer {
                             '*' => vec_last.push(Token::Op(Operator::Mul, self.line)),
                             '+' => vec_last.push(Token::Op(Operator::Add, self.line)),
                             '~' => vec_last.push(Token::Op(Operator::Invert, self.line)),
+                            '&' => vec_last.push(Token::Op(Operator::BitAnd, self.line)),
+                            '|' => vec_last.push(Token::Op(Operator::BitOr, self.line)),
                             ';' => {
                                 self.bindpair.pop();
                                 vec_last.push(Token::Op(Operator::PopBindings, self.line))
                             }
                             '=' => {
-                                if let Some(Token::Op(Operator::VariableAssign, _)) =
-                                    vec_last.last()
-                                {
+                                // == != <= >= and := are read from the op before the =
+                                let comparison = match vec_last.last() {
+                                    Some(Token::Symbol(':')) => Some(Operator::ConstAssign),
+                                    Some(Token::Op(Operator::VariableAssign, _)) => {
+                                        Some(Operator::Equals)
+                                    }
+                                    Some(Token::Op(Operator::Not, _)) => Some(Operator::NotEquals),
+                                    Some(Token::Op(Operator::Lss, _)) => Some(Operator::LssEq),
+                                    Some(Token::Op(Operator::Gtr, _)) => Some(Operator::GtrEq),
+                                    _ => None,
+                                };
+                                if let Some(comparison) = comparison {
                                     vec_last.pop();
-                                    vec_last.push(Token::Op(Operator::Equals, self.line))
+                                    vec_last.push(Token::Op(comparison, self.line))
                                 } else {
                                     vec_last.push(Token::Op(Operator::VariableAssign, self.line))
                                 }
@@ -513,6 +1138,7 @@ impl Lexer {
                         Some(Token::Op(Operator::VariableAssign, _)) => {}
                         Some(Token::Symbol(':')) => {}
                         Some(Token::Symbol('$')) => {}
+                        _ if self.ends_with_return_type() => {}
                         _ => self.add_token(Token::Symbol(',')),
                     }
 
@@ -524,17 +1150,38 @@ impl Lexer {
                     self.check_token();
                     if let Some(list) = self.tokens.pop() {
                         match self.last_token() {
-                            Some(Token::Symbol(':')) => {
+                            // the return type goes at the end of the inputs as -> int
+                            Some(_) if self.ends_with_return_type() => {
                                 if let Some(vec_last) = self.tokens.last_mut() {
-                                    vec_last.pop();
-                                    if let Some(Token::Block(Block::List(inputs))) = vec_last.pop()
+                                    if let (Some(ty), _, Some(Token::Block(Block::List(inputs)))) =
+                                        (vec_last.pop(), vec_last.pop(), vec_last.pop())
                                     {
+                                        let mut inputs = inputs.to_vec();
+                                        inputs.push(Token::Op(Operator::BindVar, self.line));
+                                        inputs.push(ty);
                                         vec_last.push(Token::Block(Block::Function(
-                                            inputs,
+                                            Rc::new(inputs),
                                             Rc::new(list),
                                         )))
-                                    } else {
-                                        todo!()
+                                    }
+                                }
+                            }
+                            Some(Token::Symbol(':')) => {
+                                if let Some(vec_last) = self.tokens.last_mut() {
+                                    vec_last.pop();
+                                    match vec_last.pop() {
+                                        Some(Token::Block(Block::List(inputs))) => vec_last.push(
+                                            Token::Block(Block::Function(inputs, Rc::new(list))),
+                                        ),
+                                        // a match arm such as `0: { ... }`
+                                        last => {
+                                            if let Some(last) = last {
+                                                vec_last.push(last);
+                                            }
+                                            vec_last.push(Token::Symbol(':'));
+                                            vec_last
+                                                .push(Token::Block(Block::Literal(Rc::new(list))))
+                                        }
                                     }
                                 }
                             }
@@ -542,7 +1189,7 @@ impl Lexer {
                                 //println!("comptime eval:");
                                 let mut vm = novacore::new();
                                 vm.init();
-                                
+
                                 vm.evaluator.evaluate(Rc::new(vm.parser.parse(list)));
                                 if let Some(vec_last) = self.tokens.last_mut() {
                                     vec_last.pop();
@@ -551,7 +1198,7 @@ impl Lexer {
                                     }
                                 }
                             }
-                            Some(_) => {self.add_token(Token::Block(Block::Literal(Rc::new(list))))}
+                            Some(_) => self.add_token(Token::Block(Block::Literal(Rc::new(list)))),
                             None => self.add_token(Token::Block(Block::Literal(Rc::new(list)))),
                         }
                     }
@@ -737,6 +1384,68 @@ impl Lexer {
                                     );
                                     std::process::exit(1)
                                 }
+                            } else if let Some(Token::Op(Operator::Mod, _)) = vec_last.last() {
+                                // %[key: value] is a dict
+                                vec_last.pop();
+                                match dict_literal(list) {
+                                    // made when lexing if every key and value is
+                                    // already a value
+                                    Ok(pairs)
+                                        if pairs.iter().all(|(key, value)| {
+                                            !matches!(key, Token::Id(_))
+                                                && value.len() == 1
+                                                && !matches!(value[0], Token::Id(_))
+                                        }) =>
+                                    {
+                                        let mut data = DictMap::default();
+                                        for (key, mut value) in pairs {
+                                            let key = match key.dict_key() {
+                                                Ok(key) => key,
+                                                Err(err) => {
+                                                    println!();
+                                                    println!("{}: {}", "LEXING ERROR".red(), err);
+                                                    std::process::exit(1)
+                                                }
+                                            };
+                                            data.insert(key, value.remove(0));
+                                        }
+                                        vec_last.push(Token::Block(Block::Dict(Rc::new(data))))
+                                    }
+                                    // otherwise dict_of({ key, value, ... }) makes it when run
+                                    Ok(pairs) => {
+                                        let mut block = vec![];
+                                        for (key, value) in pairs {
+                                            if !block.is_empty() {
+                                                block.push(Token::Symbol(','));
+                                            }
+                                            block.push(key);
+                                            block.push(Token::Symbol(','));
+                                            block.extend(value);
+                                        }
+                                        let line = self.line;
+                                        match self.function_list.get("dict_of") {
+                                            Some(&index) => {
+                                                vec_last.push(Token::Function(index, line))
+                                            }
+                                            None => {
+                                                println!();
+                                                println!(
+                                                    "{}: Dict literals need the dict_of function",
+                                                    "LEXING ERROR".red()
+                                                );
+                                                std::process::exit(1)
+                                            }
+                                        }
+                                        vec_last.push(Token::Symbol('('));
+                                        vec_last.push(Token::Block(Block::Literal(Rc::new(block))));
+                                        vec_last.push(Token::Symbol(')'));
+                                    }
+                                    Err(err) => {
+                                        println!();
+                                        println!("{}: {}", "LEXING ERROR".red(), err);
+                                        std::process::exit(1)
+                                    }
+                                }
                             } else {
                                 vec_last.push(Token::Block(Block::List(Rc::new(list))));
                             }
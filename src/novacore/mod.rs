@@ -28,7 +28,9 @@ impl Vm {
     }
 
     pub fn run_string(&mut self, input: &str) {
+        let operators = self.lexer.take_operators();
         self.lexer = lexer::new();
+        self.lexer.set_operators(operators);
         self.lexer.insert_string(input);
         self.parser = parser::new();
         self.init();
@@ -37,6 +39,12 @@ impl Vm {
             Err(_) => todo!(),
         };
         self.evaluator.evaluate(Rc::new(self.parser.parse(parsed)));
+
+        // an error in the repl can leave a loop half unwound
+        self.evaluator.state.unwind = None;
+        self.evaluator.state.loop_labels.clear();
+        self.evaluator.state.pending_label = None;
+        self.evaluator.state.try_depth = 0;
     }
 
     pub fn _get_last_in_state(&mut self) -> Option<String> {
@@ -85,6 +93,7 @@ impl Vm {
 
         // Operations
         self.add_function("free", core_ops::operator::free);
+        self.add_function("const", core_ops::operator::const_assign);
         self.add_function("return", core_ops::operator::resolve);
         self.add_function("def", core_ops::operator::variable_assign);
         self.add_function("set", core_ops::operator::variable_assign_set);
@@ -106,10 +115,38 @@ impl Vm {
         self.add_function("ischar", core_ops::casting::is_char);
 
         self.add_function("str", core_ops::casting::as_string);
+        self.add_function("isstr", core_ops::casting::is_string);
+
+        self.add_function("float", core_ops::casting::as_float);
+        self.add_function("isfloat", core_ops::casting::is_float);
+
+        self.add_function("bool", core_ops::casting::as_bool);
+        self.add_function("isbool", core_ops::casting::is_bool);
+
+        self.add_function("typeof", core_ops::casting::type_of);
+        self.add_function("islist", core_ops::casting::is_list);
+        self.add_function("isblock", core_ops::casting::is_block);
+        self.add_function("isfunc", core_ops::casting::is_func);
+        self.add_function("isstruct", core_ops::casting::is_struct);
         // math
         self.add_function("sqrt", core_ops::math::sqrt);
         self.add_function("pow", core_ops::math::pow);
         self.add_function("round", core_ops::math::round);
+        self.add_function("div", core_ops::math::floor_div);
+        self.add_function("mod", core_ops::math::floor_modulo);
+        self.add_function("quot", core_ops::math::quot);
+        self.add_function("rem", core_ops::math::rem);
+        self.add_function("wrapping_add", core_ops::math::wrapping_add);
+        self.add_function("wrapping_sub", core_ops::math::wrapping_sub);
+        self.add_function("wrapping_mul", core_ops::math::wrapping_mul);
+        self.add_function("checked_add", core_ops::math::checked_add);
+        self.add_function("checked_sub", core_ops::math::checked_sub);
+        self.add_function("checked_mul", core_ops::math::checked_mul);
+        self.add_function("checked_div", core_ops::math::checked_div);
+        self.add_function("bxor", core_ops::bitwise::bit_xor);
+        self.add_function("bnot", core_ops::bitwise::bit_not);
+        self.add_function("popcount", core_ops::bitwise::popcount);
+        self.add_function("bit", core_ops::bitwise::bit);
 
         // create
         self.add_function("range", core_ops::create::create_range);
@@ -142,6 +179,54 @@ impl Vm {
         self.add_function("last", core_ops::list::list_last);
         self.add_function("insert", core_ops::list::list_insert);
         self.add_function("remove", core_ops::list::list_remove);
+        self.add_function("map", core_ops::list::map);
+        self.add_function("filter", core_ops::list::filter);
+        self.add_function("fold", core_ops::list::fold);
+        self.add_function("reduce", core_ops::list::reduce);
+        self.add_function("reverse", core_ops::list::reverse);
+        self.add_function("zip", core_ops::list::zip);
+        self.add_function("enumerate", core_ops::list::enumerate);
+        self.add_function("flatten", core_ops::list::flatten);
+        self.add_function("take", core_ops::list::take);
+        self.add_function("skip", core_ops::list::skip);
+        self.add_function("chunk", core_ops::list::chunk);
+        self.add_function("any", core_ops::list::any);
+        self.add_function("all", core_ops::list::all);
+        self.add_function("index_of", core_ops::list::index_of);
+        self.add_function("contains", core_ops::list::contains);
+        self.add_function("unique", core_ops::list::unique);
+        self.add_function("sort_by", core_ops::list::sort_by);
+        self.add_function("iter", core_ops::iter::iter);
+        self.add_function("step", core_ops::iter::step);
+        self.add_function("take_while", core_ops::iter::take_while);
+
+        // string
+        self.add_function("split", core_ops::string::split);
+        self.add_function("join", core_ops::string::join);
+        self.add_function("trim", core_ops::string::trim);
+        self.add_function("find", core_ops::string::find);
+        self.add_function("replace", core_ops::string::replace);
+        self.add_function("substr", core_ops::string::substr);
+        self.add_function("starts_with", core_ops::string::starts_with);
+        self.add_function("ends_with", core_ops::string::ends_with);
+        self.add_function("upper", core_ops::string::upper);
+        self.add_function("lower", core_ops::string::lower);
+        self.add_function("repeat", core_ops::string::repeat);
+        self.add_function("pad_left", core_ops::string::pad_left);
+        self.add_function("pad_right", core_ops::string::pad_right);
+        self.add_function("chars", core_ops::string::chars);
+        self.add_function("len", core_ops::string::len);
+        self.add_function("format", core_ops::string::format);
+
+        // dict
+        self.add_function("dict_of", core_ops::dict::dict_of);
+        self.add_function("get", core_ops::dict::get);
+        self.add_function("put", core_ops::dict::put);
+        self.add_function("has", core_ops::dict::has);
+        self.add_function("del", core_ops::dict::del);
+        self.add_function("keys", core_ops::dict::keys);
+        self.add_function("values", core_ops::dict::values);
+        self.add_function("items", core_ops::dict::items);
 
         // //modifier
         // self.add_function("let", core_ops::modifier::closure_let);
@@ -152,6 +237,10 @@ impl Vm {
         self.add_function("func", core_ops::modifier::func);
         self.add_function("list", core_ops::modifier::list);
         self.add_function("struct", core_ops::modifier::create_struct);
+        self.add_function("with", core_ops::modifier::with);
+        self.add_function("record", core_ops::record::record);
+        self.add_function("enum", core_ops::record::declare_enum);
+        self.add_function("is", core_ops::record::is);
         self.add_function("block", core_ops::modifier::block);
         self.add_function("include", core_ops::modifier::include);
         self.add_function("memo", core_ops::modifier::memo);
@@ -161,6 +250,7 @@ impl Vm {
         self.add_function("when", core_ops::control::when_statement);
         self.add_function("con", core_ops::control::con_statement);
         self.add_function("unless", core_ops::control::unless_statement);
+        self.add_function("match", core_ops::pattern::match_statement);
         self.add_function("for", core_ops::control::for_each);
 
         //self.add_function("for", core_ops::control::for_loop);
@@ -169,6 +259,12 @@ impl Vm {
         self.add_function("each", core_ops::control::each);
         self.add_function("times", core_ops::control::times);
         self.add_function("while", core_ops::control::while_loop);
+        self.add_function("loop", core_ops::control::forever);
+        self.add_function("label", core_ops::control::label);
+        self.add_function("try", core_ops::control::try_statement);
+        self.add_function("raise", core_ops::control::raise);
+        self.add_function("break", core_ops::control::break_with);
+        self.add_function("continue", core_ops::control::continue_with);
         self.add_function("eval", core_ops::control::eval_top);
     }
 
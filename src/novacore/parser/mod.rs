@@ -1,5 +1,5 @@
 use super::core::{Operator, Token};
-use crate::novacore::core::Block;
+use crate::novacore::core::{Block, DictMap};
 use std::rc::Rc;
 
 pub struct Parser {
@@ -22,6 +22,23 @@ impl Parser {
         self.output_stack.clear();
     }
 
+    // keys and values of a dict literal are parsed like list items
+    fn parse_dict(&self, data: &DictMap) -> Token {
+        let mut parsed = DictMap::default();
+        for (key, value) in data.iter() {
+            let mut np = new();
+            if self.debug {
+                np.debug = true;
+            }
+
+            let mut pair = np.parse_list(vec![key.clone(), value.clone()]);
+            if let (Some(value), Some(key)) = (pair.pop(), pair.pop()) {
+                parsed.insert(key, value);
+            }
+        }
+        Token::Block(Block::Dict(Rc::new(parsed)))
+    }
+
     pub fn parse_list(&mut self, input: Vec<Token>) -> Vec<Token> {
         for token in input {
             match &token {
@@ -56,6 +73,22 @@ impl Parser {
                             np.parse_list(shunted.to_vec()),
                         ))));
                     }
+                    Block::Function(inputs, shunted) => {
+                        let mut np = new();
+                        if self.debug {
+                            np.debug = true;
+                        }
+
+                        self.output_stack.push(Token::Block(Block::Function(
+                            // defaults such as b={a + 1} are code
+                            Rc::new(new().parse_list(inputs.to_vec())),
+                            Rc::new(np.parse(shunted.to_vec())),
+                        )));
+                    }
+                    Block::Dict(data) => {
+                        let dict = self.parse_dict(data);
+                        self.output_stack.push(dict);
+                    }
                     _ => {
                         todo!()
                     }
@@ -76,6 +109,13 @@ impl Parser {
             match &token {
                 Token::Integer(_) => {
                     self.output_stack.push(token);
+                    // list index such as l.0
+                    if let Some(last @ Token::Op(Operator::AccessCall | Operator::AccessPath, _)) =
+                        self.operator_stack.last().cloned()
+                    {
+                        self.operator_stack.pop();
+                        self.output_stack.push(last);
+                    }
                 }
                 Token::Float(_) => {
                     self.output_stack.push(token);
@@ -83,10 +123,10 @@ impl Parser {
                 Token::String(_) => {
                     self.output_stack.push(token);
                 }
-                Token::Bool(_) => {
+                Token::Bool(_) | Token::None => {
                     self.output_stack.push(token);
                 }
-                Token::Reg(_, _) => {
+                Token::BigInteger(_) | Token::Reg(_, _) | Token::Path(_) => {
                     self.output_stack.push(token);
                 }
                 Token::Block(block) => match &block {
@@ -127,10 +167,15 @@ impl Parser {
                         }
 
                         self.operator_stack.push(Token::Block(Block::Function(
-                            inputs.clone(),
+                            // defaults such as b={a + 1} are code
+                            Rc::new(new().parse_list(inputs.to_vec())),
                             Rc::new(np.parse(shunted.to_vec())),
                         )));
                     }
+                    Block::Dict(data) => {
+                        let dict = self.parse_dict(data);
+                        self.output_stack.push(dict);
+                    }
                     _ => {
                         todo!()
                     }
@@ -165,9 +210,13 @@ impl Parser {
                                     Token::Block(block) => match block {
                                         Block::Literal(_) => todo!(),
                                         Block::Lambda(_) => self.output_stack.push(last.clone()),
-                                        Block::Function(_, _) => todo!(),
+                                        Block::Function(..) | Block::Method(..) => todo!(),
                                         Block::List(_) => todo!(),
                                         Block::Struct(_) => todo!(),
+                                        // values, so they go out as they are
+                                        Block::Dict(_) | Block::Iter(_) => {
+                                            self.output_stack.push(last.clone())
+                                        }
                                     },
                                     Token::Function(_, _) => self.output_stack.push(last.clone()),
                                     _ => self.operator_stack.push(last.clone()),
@@ -182,7 +231,9 @@ impl Parser {
                     if let Some(last) = self.operator_stack.last().cloned() {
                         if let Token::Op(function, _) = last.clone() {
                             match function {
-                                Operator::AccessCall | Operator::ModuleCall => {
+                                Operator::AccessCall
+                                | Operator::AccessPath
+                                | Operator::ModuleCall => {
                                     self.operator_stack.pop();
                                     self.output_stack.push(last);
                                 }
@@ -200,12 +251,21 @@ impl Parser {
                     | Operator::Div
                     | Operator::Equals
                     | Operator::VariableAssign
+                    | Operator::ConstAssign
                     | Operator::Not
                     | Operator::Mod
                     | Operator::And
                     | Operator::Or
+                    | Operator::NotEquals
                     | Operator::Gtr
                     | Operator::Lss
+                    | Operator::GtrEq
+                    | Operator::LssEq
+                    | Operator::BitAnd
+                    | Operator::BitOr
+                    | Operator::Shl
+                    | Operator::Shr
+                    | Operator::Infix(_)
                     | Operator::Invert => {
                         //Pop off higher precedence before adding
 
@@ -249,6 +309,7 @@ impl Parser {
                         self.output_stack.push(token)
                     }
                     Operator::UserFunctionChain
+                    | Operator::ArgStart
                     | Operator::New
                     | Operator::ResolveBind
                     | Operator::BindVar => self.output_stack.push(token),
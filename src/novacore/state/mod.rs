@@ -1,8 +1,11 @@
 use crate::novacore::utilities::print_line;
 
-use super::core::Token;
+use super::core::{Instructions, Token};
+use super::core_ops::control::Signature;
+use super::core_ops::pattern::Arm;
 use colored::Colorize;
-use fxhash::FxHashMap as HashMap;
+use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use std::rc::{Rc, Weak};
 
 pub fn read_lines<P>(
     filename: P,
@@ -14,11 +17,64 @@ where
     Ok(std::io::BufRead::lines(std::io::BufReader::new(file)))
 }
 
+/// What was worked out from a list of tokens, such as the arms of a match,
+/// kept by the address of the list. Only a `Weak` to the list is held, so it
+/// can still be freed but its address is not reused while the entry is here.
+/// Entries for freed lists are swept out each time the cache doubles.
+pub struct Compiled<T> {
+    entries: HashMap<usize, (Weak<Vec<Token>>, Rc<T>)>,
+    sweep_at: usize,
+}
+
+impl<T> Default for Compiled<T> {
+    fn default() -> Self {
+        Compiled {
+            entries: HashMap::default(),
+            sweep_at: 64,
+        }
+    }
+}
+
+impl<T> Compiled<T> {
+    pub fn get(&self, source: &Instructions) -> Option<Rc<T>> {
+        self.entries
+            .get(&(Rc::as_ptr(source) as usize))
+            .map(|(_, compiled)| compiled.clone())
+    }
+
+    pub fn insert(&mut self, source: &Instructions, compiled: Rc<T>) {
+        if self.entries.len() >= self.sweep_at {
+            self.entries
+                .retain(|_, (source, _)| source.strong_count() > 0);
+            self.sweep_at = (self.entries.len() * 2).max(64);
+        }
+        self.entries.insert(
+            Rc::as_ptr(source) as usize,
+            (Rc::downgrade(source), compiled),
+        );
+    }
+}
+
+/// A non-local exit that is travelling up through `evaluate` until the loop
+/// it targets settles it. A `None` label targets the innermost loop. An
+/// `Error` is raised inside a `try` and is settled by it.
+#[derive(Debug)]
+pub enum Unwind {
+    Break(Option<String>, Option<Token>),
+    Continue(Option<String>),
+    Error(Token),
+}
+
+#[allow(dead_code)]
 pub struct State {
     pub debug: bool,
     pub execution_stack: Vec<Token>,
     pub auxiliary: Vec<Token>,
     pub call_stack: Vec<HashMap<String, Token>>,
+    // names declared with const, one set for each scope in the call_stack
+    pub constants: Vec<HashSet<String>>,
+    // stack heights where the arguments of the calls being made begin
+    pub arg_marks: Vec<usize>,
     pub bindings: Vec<HashMap<String, Token>>,
     pub modules: HashMap<String, HashMap<String, Token>>,
     pub error_log: Vec<String>,
@@ -26,25 +82,90 @@ pub struct State {
     pub traceback: Vec<(String, usize)>,
     pub current_file: String,
     pub function_list: HashMap<String, usize>,
-    pub break_loop: Vec<bool>,
-    pub continue_loop: Vec<bool>,
+    pub unwind: Option<Unwind>,
+    pub loop_labels: Vec<Option<String>>,
+    pub pending_label: Option<(String, usize)>,
+    pub try_depth: usize,
     pub exit: bool,
     pub repl_mode: bool,
     pub cache: Option<(String, Token)>,
     pub memo: HashMap<i128, Token>,
     pub memoize: bool,
+    pub match_arms: Compiled<Vec<Arm>>,
+    pub signatures: Compiled<Signature>,
 }
 
 impl State {
+    pub fn push_scope(&mut self, scope: HashMap<String, Token>) {
+        self.call_stack.push(scope);
+        self.constants.push(HashSet::default());
+    }
+
+    pub fn pop_scope(&mut self) -> Option<HashMap<String, Token>> {
+        self.constants.pop();
+        self.call_stack.pop()
+    }
+
+    pub fn truncate_scopes(&mut self, len: usize) {
+        self.call_stack.truncate(len);
+        self.constants.truncate(len);
+    }
+
+    pub fn mark_arguments(&mut self) {
+        self.arg_marks.push(self.execution_stack.len());
+    }
+
+    // How many values were pushed since the arguments of the current call
+    // began, or None when the call was made without a mark
+    pub fn take_arg_count(&mut self) -> Option<usize> {
+        self.arg_marks
+            .pop()
+            .map(|mark| self.execution_stack.len().saturating_sub(mark))
+    }
+
+    pub fn is_constant(&self, ident: &str) -> bool {
+        self.constants
+            .last()
+            .is_some_and(|constants| constants.contains(ident))
+    }
+
     pub fn add_varaible(&mut self, ident: &str, item: Token) {
         if ident != "_" {
+            if self.is_constant(ident) {
+                self.show_error(&format!("Cannot reassign constant {}", ident));
+                return;
+            }
             if let Some(scope) = self.call_stack.last_mut() {
                 scope.insert(ident.to_string(), item);
             }
         }
     }
 
+    pub fn add_constant(&mut self, ident: &str, item: Token) {
+        self.add_varaible(ident, item);
+        if ident != "_" && self.unwind.is_none() {
+            if let Some(constants) = self.constants.last_mut() {
+                constants.insert(ident.to_string());
+            }
+        }
+    }
+
+    pub fn is_loop_label(&self, ident: &str) -> bool {
+        self.loop_labels
+            .iter()
+            .any(|label| label.as_deref() == Some(ident))
+    }
+
     pub fn show_error(&mut self, err: &str) {
+        // inside a try the error unwinds to it instead, keeping the first
+        // error when a failed lookup is followed by a missing argument
+        if self.try_depth > 0 {
+            if !matches!(self.unwind, Some(Unwind::Error(_))) {
+                self.unwind = Some(Unwind::Error(Token::String(err.to_string())));
+            }
+            return;
+        }
+
         println!();
         self.traceback.reverse();
         while let Some(function_call) = self.traceback.pop() {
@@ -58,13 +179,29 @@ impl State {
         }
     }
 
+    // a constant stays, so a loop variable that failed to shadow one can not
+    // take it with it
     pub fn remove_varaible(&mut self, ident: &str) {
+        if self.is_constant(ident) {
+            return;
+        }
         if let Some(scope) = self.call_stack.last_mut() {
             scope.remove(ident);
         }
     }
 
     pub fn move_varaible(&mut self, ident: &str, newident: &str) {
+        if self.is_constant(ident) || self.is_constant(newident) {
+            self.show_error(&format!(
+                "Cannot move constant {}",
+                if self.is_constant(ident) {
+                    ident
+                } else {
+                    newident
+                }
+            ));
+            return;
+        }
         if let Some(scope) = self.call_stack.last_mut() {
             if let Some(moved) = scope.remove(ident) {
                 scope.insert(newident.to_string(), moved);
@@ -95,7 +232,7 @@ impl State {
         let tok = self.execution_stack.pop()?;
 
         if let Token::Id(ident) = tok {
-            if let Some(scopes) = self.bindings.last_mut() {
+            if let Some(scopes) = self.bindings.last() {
                 if let Some(token) = scopes.get(&ident) {
                     return Some(token.clone());
                 }
@@ -125,6 +262,8 @@ pub fn new() -> Box<State> {
     Box::new(State {
         execution_stack: Vec::with_capacity(1024),
         call_stack: vec![HashMap::default()],
+        constants: vec![HashSet::default()],
+        arg_marks: vec![],
         auxiliary: vec![],
         debug: false,
         error_log: vec![],
@@ -132,8 +271,10 @@ pub fn new() -> Box<State> {
         traceback: vec![],
         current_file: "".to_string(),
         function_list: HashMap::default(),
-        break_loop: vec![],
-        continue_loop: vec![],
+        unwind: None,
+        loop_labels: vec![],
+        pending_label: None,
+        try_depth: 0,
         exit: false,
         bindings: vec![HashMap::default()],
         repl_mode: false,
@@ -141,5 +282,7 @@ pub fn new() -> Box<State> {
         cache: None,
         memo: HashMap::default(),
         memoize: false,
+        match_arms: Compiled::default(),
+        signatures: Compiled::default(),
     })
 }
@@ -0,0 +1,61 @@
+use std::{fs, process::Command};
+
+// Runs tests/scripts/<name>.nc and compares what it prints with <name>.out
+fn run(name: &str) {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let output = Command::new(env!("CARGO_BIN_EXE_novacore"))
+        .arg(format!("tests/scripts/{}.nc", name))
+        .current_dir(root)
+        .output()
+        .expect("could not run novacore");
+    assert!(
+        output.status.success(),
+        "{}.nc exited with {}",
+        name,
+        output.status
+    );
+    let expected = fs::read_to_string(format!("{}/tests/scripts/{}.out", root, name))
+        .expect("missing expected output")
+        .replace("\r\n", "\n");
+    let actual = String::from_utf8_lossy(&output.stdout).replace("\r\n", "\n");
+    assert_eq!(actual, expected, "output of {}.nc", name);
+}
+
+macro_rules! scripts {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                run(stringify!($name));
+            }
+        )*
+    };
+}
+
+scripts!(
+    loops,
+    patterns,
+    dicts,
+    strings,
+    interpolation,
+    structs,
+    methods,
+    bigints,
+    arithmetic,
+    escapes,
+    numbers,
+    comparisons,
+    ordering,
+    bits,
+    lists,
+    ranges,
+    types,
+    none,
+    consts,
+    params,
+    annotations,
+    records,
+    enums,
+    protocols,
+    infix
+);
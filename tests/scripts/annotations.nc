@@ -0,0 +1,21 @@
+add = [x:int y:int]:int { return(x + y) }
+println(add(2 3))
+scale = [x:float by:float=2.0]: { x * by }
+println(scale(1.5))
+greet = [name:string ..rest:int]:string { return(name) }
+println(greet("hi" 1 2 3))
+plain = [a b]: { a + b }
+println(plain(1 2))
+nothing = []:none { none }
+println(nothing())
+try({ add("a" 2) } { println() })
+try({ greet("x" 1 "b") } { println() })
+bad = [x:int]:string { x }
+try({ bad(1) } { println() })
+try({ call(1 bad) } { println() })
+try({ map([1 2] [x:string]: { x }) } { println() })
+println(map([1 2] [x:int]:int { x * 2 }))
+f = [x:foo]: { x }
+try({ f(1) } { println() })
+g = [x:]: { x }
+try({ g(1) } { println() })
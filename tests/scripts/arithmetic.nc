@@ -0,0 +1,11 @@
+println(7 / 2)
+println(div(-7 2))
+println(quot(-7 2))
+println(mod(-7 2))
+println(rem(-7 2))
+println(-7 % 2)
+println(wrapping_add(170141183460469231731687303715884105727 1))
+try({ checked_add(170141183460469231731687303715884105727 1) } { println() })
+try({ checked_div(1 0) } { println() })
+try({ div(1 0) } { println() })
+println(checked_mul(6 7))
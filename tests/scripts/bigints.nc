@@ -0,0 +1,13 @@
+import([std])
+println(std::factorial(30))
+println(2 pow(130))
+big = 2 pow(127)
+println(big * 2 - big * 2 + 1)
+println(typeof(2 pow(200)) == typeof(1))
+println(2 pow(128) / 2 pow(127))
+println(0 - 2 pow(130) < 0)
+println(2 pow(128) == 2 pow(128))
+big = 100000000000000000000000
+println(match(big [ 100000000000000000000000: { "big" } _: { "other" } ]))
+bigger = big + 1
+println(match(bigger [ 100000000000000000000000: { "big" } _: { "other" } ]))
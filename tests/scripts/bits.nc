@@ -0,0 +1,17 @@
+flags = 0b1100 | 0b0011
+println(flags & 0b0110)
+println(bxor(flags 1 << 2))
+println(popcount(flags))
+println(bit(flags 3))
+println(-1 & 255)
+println(-8 >> 1)
+println(bnot(0))
+println(popcount(-1))
+println(popcount(-2))
+println(popcount(2 pow(200)))
+println(1 << 130 >> 129)
+try({ popcount(0 - 2 pow(200)) } { println() })
+try({ 1 << 100000000000 } { println() })
+println(len(str(1 << 100000)))
+println(0 - 8 >> 100000000000)
+println(8 >> 100000000000)
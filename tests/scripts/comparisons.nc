@@ -0,0 +1,7 @@
+println(3 <= 3 and 3 >= 3)
+println(2 != 3)
+println("apple" < "banana")
+println('a' != 'b')
+println('b' >= 'a')
+println("abc" <= "abd")
+println(1 > 2)
@@ -0,0 +1,20 @@
+pi := 3.14
+println(pi)
+const(e 2.71)
+println(e + 1)
+f = [x]: {
+  pi := x
+  pi * 2
+}
+println(f(5))
+println(pi)
+try({ pi = 4 } { println() })
+try({ e = 4 } { println() })
+try({ free(pi) } { println() })
+try({ pi := 1 } { println() })
+try({ for(pi [1 2] { println(pi) }) } { println() })
+try({ const(1 2) } { println() })
+println(pi)
+x = 1
+x = 2
+println(x)
@@ -0,0 +1,48 @@
+ages = %["ann lee": 31 7: "seven" 'c': [1 2]]
+ages = put(ages "bob" 40)
+println(get(ages "bob"))
+println(get(ages 7.0))
+println(has(ages 7))
+println(get(ages "carol") == none)
+println(len(ages))
+
+nums = %[1: "one" 2.5: "two and a half"]
+println(get(nums 1.0))
+println(get(nums 2.5))
+nums = put(nums 1.0 "uno")
+println(len(nums))
+println(get(nums 1))
+nums = del(nums 1.0)
+println(has(nums 1))
+
+lists = put(%[] [1 2] "pair")
+println(get(lists [1.0 2]))
+
+vec = [x y]:{new}
+points = put(%[] vec(1 2) "a")
+println(get(points vec(1.0 2)))
+println(%[1: 2] == %[1.0: 2])
+println(keys(put(%[] "k" "v")))
+try({ put(%[] sqrt(-1.0) 1) } { println() })
+
+double = [a]: { return(a * 2) }
+x = 5
+k = "key"
+made = %["x": x k: 2 "sum": 1 + 2 "call": double(4) "list": [1 2]]
+println(get(made "x"))
+println(get(made "key"))
+println(get(made "sum"))
+println(get(made "call"))
+println(get(made "list"))
+scaled = [y]: { return(%["y": y * 10]) }
+println(get(scaled(4) "y"))
+n = sqrt(-1.0)
+try({ %[n: 1] } { println() })
+
+order = %["b": 1 "a": 2 "c": 3]
+println(order)
+println(keys(put(del(order "b") "b" 4)))
+for( pair order {
+  println(pair)
+})
+println(%["z": 1 "a": 2] == %["a": 2 "z": 1])
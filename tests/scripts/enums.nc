@@ -0,0 +1,36 @@
+enum(shape [circle(r) rect(w h) empty])
+c = shape::circle(2)
+r = shape::rect(3 4)
+e = shape::empty
+println(c)
+println(r)
+println(e)
+println(shape::circle)
+println(typeof(c))
+println(is(shape::circle c))
+println(is(shape::rect c))
+println(is(shape::empty e))
+println(is(shape::empty c))
+println(c.r)
+area = [s]: {
+  match( s [
+    shape::circle(r): { r^ * r^ * 3 }
+    shape::rect(w h): { w^ * h^ }
+    shape::empty: { 0 }
+  ])
+}
+println(area(c))
+println(area(r))
+println(area(e))
+println(map([c r e] area))
+println(match(r [ rect(w 4): { w^ } _: { 0 } ]))
+println(match(r [ rect(w 5): { w^ } _: { 0 } ]))
+println(c == shape::circle(2))
+record(point [x y])
+println(match(point(1 2) [ point(a b): { a^ + b^ } ]))
+println(is(point point(1 2)))
+println(is(point c))
+try({ shape::circle(1 2) } { println() })
+try({ enum(bad [a(x x)]) } { println() })
+try({ is(5 c) } { println() })
+try({ shape::square(1) } { println() })
@@ -0,0 +1,6 @@
+println("name\tage\n\"ann\"\t31")
+println("\x41\u{e9}\u{1F600}")
+println('\n' == '\x0A')
+println(len("a\\b"))
+println('\'')
+println("\0" == "\x00")
@@ -0,0 +1,25 @@
+infix(<> 13 left my_concat)
+my_concat = [a b]: { return(str(a) + str(b)) }
+println(1 <> 2)
+println(1 <> 2 <> 3)
+println(1 + 2 <> 3)
+println("a" <> 4 * 2)
+infix(** 15 right power)
+power = [a b]: { pow(a b) }
+println(2 ** 3 ** 2)
+infix(?? 4 left or_else)
+or_else = [a b]: { if(a == none { return(b) } { return(a) }) }
+println(none ?? 5)
+println(3 ?? 5)
+infix(|> 3 left pipe)
+pipe = [x f]: { call(x f) }
+double = [x]: { x * 2 }
+println(4 |> double |> double)
+infix(+++ 13 left pow)
+println(2 +++ 5)
+xs = [1 2 3]
+println(map(xs [x]: { x <> "!" }))
+println("x <> y in a string")
+println("{1 <> 2}")
+println(1 < 2)
+println(8 >> 1)
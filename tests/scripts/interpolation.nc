@@ -0,0 +1,36 @@
+x = 3
+y = 2.345
+println("pos {x}, {y:.2}, sum {x + 4}")
+println("{x}")
+println("{x:>4}|{255:x}|{5:0>8b}")
+println("name: {upper("bob")} {'c'}")
+println("{x + "}"} has a brace in a string")
+println("escaped {{x}} and \{ }")
+println("lone { brace")
+println("close } alone")
+println(format("{} and {:>3}" [1 2]))
+vec = [x y]:{new}
+v = vec(1 2)
+println("v is {v.x}, {v.y}")
+neg = 0 - 3.14159
+println(format("[{:08.3}]" [3.14159]))
+println(format("[{:08.3}]" [neg]))
+m = 0 - 42
+println(format("[{:05}]" [m]))
+println(format("[{:06x}]" [255]))
+println(format("[{:.2}]" [100000000000000000000000]))
+println(format("[{:.2}]" [7]))
+println(format("[{:.0}]" [7]))
+println(format("[{:>8.1}]" [2.25]))
+println(format("[{:0>6}]" [42]))
+println(format("[{:03}]" [12345]))
+pi = 3.14159
+println("[{pi:08.3}]")
+try({ format("{:05}" ["ab"]) } { println() })
+try({ format("{{:+5}}" [1]) } { println() })
+xs = [1 2]
+println("xs = {xs}")
+record(point [x y])
+p = point(1 2)
+println("p = {p}")
+println("{xs} and {p}")
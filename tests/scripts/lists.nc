@@ -0,0 +1,21 @@
+l = [3 1 4 1 5 9]
+println(map(l { [x] -> x^ * 2 ; }))
+println(filter(l { [x] -> x^ > 2 ; }))
+println(fold(l 0 {+}))
+println(reduce(l {+}))
+println(sort_by(["ccc" "a" "bb"] { len() }))
+println(find(l { [x] -> x^ > 3 ; }))
+println(find(l { [x] -> x^ > 30 ; }) == none)
+println(index_of(l 4))
+println(index_of(l 7) == none)
+println(unique(l))
+println(unique([1 1.0 [2] [2.0] "a" 'a']))
+println(len(unique([nan nan])))
+println(zip([1 2] ["a" "b"]))
+println(enumerate(["x" "y"]))
+println(flatten([[1 2] [3] []]))
+println(take(l 2))
+println(skip(l 4))
+println(chunk(l 4))
+println(any(l { [x] -> x^ > 8 ; }) and all(l { [x] -> x^ > 0 ; }))
+println(reverse([1 2 3]))
@@ -0,0 +1,25 @@
+n = 0
+found = loop({
+  n = n + 1
+  when( n * n > 50 { break(n) })
+})
+println(found)
+
+hits = 0
+label(outer) for( i [1 2 3] {
+  each( [1 2 3] {
+    [j] -> when( i * j^ > 3 { break(outer) }) ;
+    hits = hits + 1
+  })
+})
+println(hits)
+
+odd = 0
+for( i [1 2 3 4 5] {
+  when( i % 2 == 0 { continue })
+  odd = odd + i
+})
+println(odd)
+
+try({ break } { println() })
+try({ continue } { println() })
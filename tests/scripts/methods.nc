@@ -0,0 +1,25 @@
+counter = [n]: {
+  inc = []: { with(self { n = self.n + 1 }) }
+  add = [k]: { with(self { n = self.n + k }) }
+  count = []: { self.n }
+  new
+}
+println(counter(0).inc().inc().count())
+c = counter(5)
+println(c.add(10).count())
+println(c.count())
+p = [x]: {
+  scaled = [k]: { self.x * k }
+  new
+}
+q = p(3)
+f = q.scaled
+println(f(2))
+println(map([1 2 3] q.scaled))
+println(typeof(q.scaled))
+println(isfunc(q.scaled))
+n = 0
+times( 1000 {
+  n = n + q.scaled(1)
+})
+println(n)
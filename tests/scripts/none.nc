@@ -0,0 +1,29 @@
+import([std])
+println(10 < 9.5)
+println(2 < 10.5)
+println(1 == 1.0)
+println([1 2] == [1.0 2])
+println(std::sort([3 1.5 2 0.5]))
+l = [1 2 4 5]
+println(index_of(l 4.0))
+println(contains(l 5.0))
+d = %["a": 1]
+println(get(d "b"))
+println(get(d "b") == none)
+println(get(d "a") == none)
+println(none != none)
+println(typeof(none))
+p = [x y]: { new }
+println(get(p(1 2) "x"))
+println(get(p(1 2) "z"))
+println(get([1 2 3] 5))
+println(get([1 2 3] 1))
+println(find([1 2 3] { [x] -> x^ > 5 ; }))
+println(last([]))
+println([1 none 2])
+println("got {get(d "q")}")
+println(str(none))
+println(match(none [ none: { "nothing" } _: { "?" } ]))
+println(sort_by([3 none 1] {}))
+x = none
+println(x)
@@ -0,0 +1,9 @@
+println(0xFF + 0b1010 + 0o17)
+println(1_000_000 * 1.5e-3)
+println([1 -2 3])
+x = 5
+println(x-1)
+println(2.5e2)
+println(inf > 1e300)
+println(nan == nan)
+println(0x_ff)
@@ -0,0 +1,23 @@
+import([std])
+vec = [x y]: { new }
+println([1 2] == [1 2] and [1 2] < [1 3])
+println([1 2] < [1 2 0])
+println(vec(1 2) == vec(1 2))
+println(vec(1 2) != vec(2 1))
+println(%[1: 2] == %[1: 2])
+println(true < 1)
+println(1 < 'a')
+println('a' < "a")
+println("z" < [1])
+println(std::max([[1 2] [1 3] [0 9]]))
+println(std::min(["pear" "apple" "fig"]))
+println(nan == nan)
+println(match(1.0 [ 1: { "one" } _: { "other" } ]))
+println(match(2 [ 2.0: { "two" } _: { "other" } ]))
+println(match(nan [ nan: { "nan" } _: { "other" } ]))
+println(9007199254740993 == 9007199254740992.0)
+println(9007199254740992 == 9007199254740992.0)
+println(9007199254740993 > 9007199254740992.0)
+println(2 pow(130) == 2.0 pow(130))
+println(2 pow(130) + 1 > 2.0 pow(130))
+println(%[9007199254740992.0: "f" 9007199254740993: "i"])
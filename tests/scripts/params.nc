@@ -0,0 +1,33 @@
+f = [a b=10 ..rest]: { println(a) println(b) println(rest) }
+f(1)
+f(1 2)
+f(1 2 3 4)
+g = [x y={x * 2}]: { x + y }
+println(g(3))
+println(g(3 1))
+println(5 g())
+add = [x y]: { x + y }
+println(add(3 5))
+println(5 add(5))
+println(5 5 add())
+z = 100
+h = [n=z]: { return(n) }
+println(h())
+println(h(7))
+println(call(1 g))
+println(call(1 2 g))
+println(exe(4 g))
+s = [v]: {
+  m = [k=1 ..more]: { self.v + k + len(more) }
+  new
+}
+println(s(10).m())
+println(s(10).m(5 1 1))
+println(map([1 2] g))
+println(add(g(1) h()))
+bad = [a=1 b]: { a }
+bad = [a=1 b]: { a }
+try({ bad(1 2) } { println() })
+late = [..rest a]: { a }
+try({ late(1 2) } { println() })
+try({ add(1) } { println() })
@@ -0,0 +1,32 @@
+describe = [v]: {
+  match(v [
+    0: { "zero" }
+    int(n) if { n^ < 0 }: { "negative " + n^ }
+    int: { "positive int" }
+    "hi": { "greeting" }
+    [h ..t]: { "head " + h^ }
+    []: { "empty" }
+    {x [y 0]}: { "on x axis at " + x^ }
+    _: { "other" }
+  ])
+}
+pt = [x y]:{new}
+for( v [0 -4 9 "hi" [1 2 3] [] 'c'] {
+  println(describe(v))
+})
+println(describe(pt(3 0)))
+println(match([1 [2 3]] [ [a [b c]]: { a^ + b^ + c^ } ]))
+
+scale = [k]: {
+  k [k] -> match(3 [ n: { k^ * n^ } ]) ;
+}
+println(scale(4))
+
+try({ match(5 [ 1: { "no" } ]) } { println() })
+try({ 1 [x] -> y^ ; } { println() })
+try({ 1 [x] -> 2 [y] -> x^ ; ; } { println() })
+one = [ 1: { "one" } _: { "other" } ]
+two = [ 2: { "two" } _: { "other" } ]
+for( arms [one two one two] {
+  println(match( 2 remove(push(arms 0) 6)))
+})
@@ -0,0 +1,33 @@
+vec = [x y]: {
+  __add = [a b]: { vec(a.x + b.x, a.y + b.y) }
+  __sub = [a b]: { vec(a.x - b.x, a.y - b.y) }
+  __mul = [a b]: { vec(a.x * b, a.y * b) }
+  __div = [a b]: { vec(a.x / b, a.y / b) }
+  __eq = [a b]: { a.x == b.x and a.y == b.y }
+  __lt = [a b]: { a.x * a.x + a.y * a.y < b.x * b.x + b.y * b.y }
+  __str = [v]: { "<" + str(v.x) + ", " + str(v.y) + ">" }
+  new
+}
+a = vec(1 2)
+b = vec(3 4)
+println(a + b)
+println(b - a)
+println(a * 3)
+println(vec(4 8) / 2)
+println(a == vec(1 2))
+println(a != b)
+println(a < b)
+println(a > b)
+println(a <= vec(2 1))
+println(a >= b)
+print(a)
+println("")
+println(str(b) + "!")
+println(2 * 3)
+bad = [x]: {
+  __eq = [a b]: { 1 }
+  new
+}
+try({ bad(1) == bad(1) } { println() })
+s = struct({ q = 1 })
+try({ s + 1 } { println() })
@@ -0,0 +1,43 @@
+r = range(0 3)
+println(r)
+println(iota(3))
+println(push(r 4))
+println(r(2))
+println(r.1)
+println(get(r 1))
+println(get(r 10) == none)
+println(pop(r))
+println(last(r))
+println(insert(r 0 9))
+println(remove(r 0))
+println(contains(r 2))
+println(zip(r range(5 9)))
+println(r == [0 1 2 3])
+println(r < [0 1 2 4])
+println(join(r "-"))
+println(format("{} {}" range(1 2)))
+println(match(r [ [a ..rest]: { rest^ } ]))
+println(range(10 0) step(-3))
+println(range(0 10) step(-3))
+println(collect(reverse(range(1 10) step(3))))
+evens = filter(map(range(1 100000000) { [x] -> x^ * x^ ; }) { [x] -> x^ % 2 == 0 ; })
+println(collect(take(evens 3)))
+println(range(0 100000000)(5))
+total = 0
+for( i range(1 100) { total = total + i })
+println(total)
+try({ range(0 3) step(0) } { println() })
+println(len(range(0 100000000000)))
+println(len(range(0 10) step(3)))
+println(len(range(10 0) step(-3)))
+println(len(range(0 10) step(-3)))
+big = range(0 100000000000)
+println(big)
+println(str(big step(7)))
+println(collect(reverse(range(0 170141183460469231731687303715884105727) step(85070591730234615865843651857942052863))))
+println(len(range(0 170141183460469231731687303715884105727)))
+lo = 0 - 170141183460469231731687303715884105727 - 1
+hi = 170141183460469231731687303715884105727
+println(collect(take(reverse(range(lo hi)) 2)))
+println(len(range(lo hi)))
+try({ reverse(range(hi lo) step(lo)) } { println() })
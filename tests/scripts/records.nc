@@ -0,0 +1,28 @@
+record(point [x y])
+p = point(1 2)
+println(p)
+println(p.x + p.y)
+println(typeof(p))
+println(typeof(point))
+println(point)
+q = with(p { x = 10 })
+println(q)
+println(p)
+set(q.y 5)
+println(q)
+println(p == point(1 2))
+println(p == q)
+3 4 point()
+println()
+record(line [from to])
+l = line(point(0 0) point(3 4))
+println(l)
+set(l.to.x 9)
+println(l)
+println(match(p [ {x y}: { x^ * y^ } _: { 0 } ]))
+try({ point(1) } { println() })
+try({ point(1 2 3) } { println() })
+try({ with(p { z = 1 }) } { println() })
+try({ set(p.z 1) } { println() })
+try({ record(bad [x x]) } { println() })
+try({ record(bad [__x]) } { println() })
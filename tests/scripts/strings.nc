@@ -0,0 +1,20 @@
+words = split("one two three" " ")
+println(join(words ", "))
+println(trim("  padded  "))
+println(pad_left(str(42) 6 '0'))
+println(pad_right("ab" 4 '.'))
+println(upper(substr("héllo" 1 3)))
+println(lower("MiXeD"))
+println(find("héllo" "llo"))
+println(find("hello" "z") == none)
+println(replace("aaa" "a" "bb"))
+println(starts_with("novacore" "nova") and ends_with("novacore" "core"))
+println(repeat("ab" 3))
+println(chars("abc"))
+println(len("héllo"))
+try({ replace([1 2 3] 1 9) } { println() })
+println(substr("abc" 3 1))
+println(substr("abc" 1 9))
+try({ substr("abc" -1 1) } { println() })
+try({ substr("abc" 4 1) } { println() })
+println(find("abc" "z"))
@@ -0,0 +1,20 @@
+vec = [x y]: { new }
+ent = [name pos]: { new }
+a = vec(1 2)
+b = with(a { x = 5 })
+set(a.y 10)
+println(a.x + a.y)
+println(b.x + b.y)
+e = ent("bob" vec(1 2))
+e2 = with(e { name = "alice" })
+set(e.pos.x 10)
+println(e.name + " " + e2.name)
+println(e.pos.x)
+println(e2.pos.x)
+l = [1 [2 3] 4]
+set(l.1.0 99)
+println(l)
+20 set(e.pos.y)
+println(e.pos.y)
+p = with(e.pos { y = e.pos.y * 2 })
+println(p.y)
@@ -0,0 +1,17 @@
+println(typeof(1.5))
+println(typeof(1))
+println(typeof("s"))
+println(typeof('c'))
+println(typeof([1]))
+println(typeof(%[1: 2]))
+println(typeof({ 1 }))
+println(typeof([x]: { x }))
+println(typeof(none))
+println(float("2.5") + float(1))
+println(int("42") + 1)
+println(isfunc([x]: { x }))
+println(isint(3) and isfloat(3.0) and isstr("a") and ischar('a'))
+println(isbool(true) and islist([]) and isblock({ }))
+println(bool(0))
+println(str(12) + "!")
+println(ischar(1) or isstruct([1]))